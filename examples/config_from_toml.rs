@@ -0,0 +1,31 @@
+//! Loads the ingestion config from a TOML file instead of hardcoding it.
+//!
+//! Run with `cargo run --example config_from_toml --features serde [path]`,
+//! `path` defaults to `examples/config.toml`.
+
+use ingest::{BoundedRange, CaptiveCore, IngestionConfig, Range};
+use stellar_xdr::next::LedgerCloseMeta;
+
+pub fn main() {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "examples/config.toml".to_string());
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    let config: IngestionConfig = toml::from_str(&contents).unwrap();
+
+    println!("Running on {:?} from {}", config.network, config.context_path.0);
+
+    let mut captive_core = CaptiveCore::new(config).unwrap();
+
+    let range = Range::Bounded(BoundedRange(29, 30));
+    captive_core.prepare_ledgers_single_thread(&range).unwrap();
+
+    let ledger = captive_core.get_ledger(29);
+    let ledger_seq = match ledger.as_ref().unwrap() {
+        LedgerCloseMeta::V1(v1) => v1.ledger_header.header.ledger_seq,
+        LedgerCloseMeta::V0(v0) => v0.ledger_header.header.ledger_seq,
+    };
+
+    println!("Hello ledger {}", ledger_seq);
+}
@@ -0,0 +1,168 @@
+use std::{
+    path::Path,
+    sync::{mpsc::Receiver, Arc, Mutex},
+};
+
+use stellar_xdr::next::LedgerCloseMeta;
+
+use crate::{BoundedRange, CaptiveCore, Error, IngestionMetrics, LedgerBatch, MetaResult, Range};
+
+/// Async wrapper around a [`CaptiveCore`], returned by [`CaptiveCore::into_async`].
+///
+/// The blocking `CaptiveCore` methods, such as preparing ledgers or starting
+/// stellar-core, run on tokio's blocking thread pool through
+/// `tokio::task::spawn_blocking`, so they don't block the async runtime.
+/// The other methods are available on the `CaptiveCore` returned by
+/// [`AsyncCaptiveCore::sync`].
+pub struct AsyncCaptiveCore {
+    core: Arc<Mutex<CaptiveCore>>,
+
+    // kept aside so that metrics can be read while a blocking call holds the core.
+    metrics: Arc<IngestionMetrics>,
+}
+
+impl CaptiveCore {
+    /// Wraps the captive core to use its blocking methods from async code.
+    pub fn into_async(self) -> AsyncCaptiveCore {
+        AsyncCaptiveCore {
+            metrics: self.metrics(),
+            core: Arc::new(Mutex::new(self)),
+        }
+    }
+}
+
+impl AsyncCaptiveCore {
+    /// Returns the wrapped `CaptiveCore`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a blocking call on the captive core is still running, which
+    /// can only happen if its future was dropped before completing.
+    pub fn sync(self) -> CaptiveCore {
+        match Arc::try_unwrap(self.core) {
+            Ok(mutex) => mutex
+                .into_inner()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+            Err(_) => panic!("Captive core is still in use by a blocking task"),
+        }
+    }
+
+    // Runs `f` on the blocking thread pool, resuming its panic if it panics.
+    async fn run_blocking<T, F>(&self, f: F) -> T
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut CaptiveCore) -> T + Send + 'static,
+    {
+        let core = self.core.clone();
+        let task = tokio::task::spawn_blocking(move || {
+            let mut core = core.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            f(&mut core)
+        });
+
+        match task.await {
+            Ok(value) => value,
+            Err(error) => std::panic::resume_unwind(error.into_panic()),
+        }
+    }
+
+    /// Async version of [`CaptiveCore::prepare_ledgers_single_thread`].
+    pub async fn prepare_ledgers_single_thread(&self, range: &Range) -> Result<LedgerBatch, Error> {
+        let range = range.clone();
+        self.run_blocking(move |core| core.prepare_ledgers_single_thread(&range))
+            .await
+    }
+
+    /// Async version of [`CaptiveCore::prepare_ledgers_multi_thread`].
+    pub async fn prepare_ledgers_multi_thread(
+        &self,
+        range: &Range,
+    ) -> Result<Receiver<Box<MetaResult>>, Error> {
+        let range = range.clone();
+        self.run_blocking(move |core| core.prepare_ledgers_multi_thread(&range))
+            .await
+    }
+
+    /// Async version of [`CaptiveCore::replay_with_processor`].
+    pub async fn replay_with_processor<F>(&self, range: &Range, processor: F) -> Result<(), Error>
+    where
+        F: FnMut(LedgerCloseMeta) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+            + Send
+            + 'static,
+    {
+        let range = range.clone();
+        self.run_blocking(move |core| core.replay_with_processor(&range, processor))
+            .await
+    }
+
+    /// Async version of [`CaptiveCore::close_runner_process`].
+    pub async fn close_runner_process(&self) -> Result<(), Error> {
+        self.run_blocking(|core| core.close_runner_process()).await
+    }
+
+    /// Async version of [`CaptiveCore::get_ledger`].
+    pub async fn get_ledger(&self, sequence: u32) -> Result<LedgerCloseMeta, Error> {
+        self.run_blocking(move |core| core.get_ledger(sequence))
+            .await
+    }
+
+    /// Async version of [`CaptiveCore::get_ledgers_in_range`].
+    pub async fn get_ledgers_in_range(
+        &self,
+        range: &BoundedRange,
+    ) -> Result<Vec<LedgerCloseMeta>, Error> {
+        let range = *range;
+        self.run_blocking(move |core| core.get_ledgers_in_range(&range))
+            .await
+    }
+
+    /// Async version of [`CaptiveCore::verify_sequence_continuity`].
+    pub async fn verify_sequence_continuity(&self) -> Result<(), Error> {
+        self.run_blocking(|core| core.verify_sequence_continuity())
+            .await
+    }
+
+    /// Async version of [`CaptiveCore::export_prepared_to_xdr_file`].
+    pub async fn export_prepared_to_xdr_file(&self, path: &Path) -> Result<(), Error> {
+        let path = path.to_path_buf();
+        self.run_blocking(move |core| core.export_prepared_to_xdr_file(&path))
+            .await
+    }
+
+    /// Async version of [`CaptiveCore::import_prepared_from_xdr_file`].
+    pub async fn import_prepared_from_xdr_file(&self, path: &Path) -> Result<(), Error> {
+        let path = path.to_path_buf();
+        self.run_blocking(move |core| core.import_prepared_from_xdr_file(&path))
+            .await
+    }
+
+    /// Async version of [`CaptiveCore::resume_from_checkpoint`].
+    pub async fn resume_from_checkpoint(
+        &self,
+        path: &Path,
+        target_end: u32,
+    ) -> Result<Receiver<Box<MetaResult>>, Error> {
+        let path = path.to_path_buf();
+        self.run_blocking(move |core| core.resume_from_checkpoint(&path, target_end))
+            .await
+    }
+
+    /// Async version of [`CaptiveCore::start_online_no_range`].
+    pub async fn start_online_no_range(&self) -> Result<Receiver<Box<MetaResult>>, Error> {
+        self.run_blocking(|core| core.start_online_no_range()).await
+    }
+
+    /// Async version of [`CaptiveCore::start_online_with_initial_catchup`].
+    pub async fn start_online_with_initial_catchup(
+        &self,
+        lookback_ledgers: u32,
+    ) -> Result<Receiver<Box<MetaResult>>, Error> {
+        self.run_blocking(move |core| core.start_online_with_initial_catchup(lookback_ledgers))
+            .await
+    }
+
+    /// Returns the metrics of the wrapped captive core. Unlike the other
+    /// methods, this doesn't wait for running blocking calls.
+    pub fn metrics(&self) -> Arc<IngestionMetrics> {
+        self.metrics.clone()
+    }
+}
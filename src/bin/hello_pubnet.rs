@@ -8,13 +8,11 @@ use stellar_xdr::next::LedgerCloseMeta;
 pub fn main() {
     let config = IngestionConfig {
         executable_path: "/usr/local/bin/stellar-core".to_string(),
-        context_path: Default::default(),
         network: SupportedNetwork::Pubnet,
-        bounded_buffer_size: None,
-        staggered: None,
+        ..Default::default()
     };
 
-    let mut captive_core = CaptiveCore::new(config);
+    let mut captive_core = CaptiveCore::new(config).unwrap();
 
     let range = Range::Bounded(BoundedRange(292395, 292396));
     captive_core.prepare_ledgers_single_thread(&range).unwrap();
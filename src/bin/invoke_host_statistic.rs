@@ -7,13 +7,11 @@ use stellar_xdr::next::{
 pub fn main() {
     let config = IngestionConfig {
         executable_path: "/usr/local/bin/stellar-core".to_string(),
-        context_path: Default::default(),
         network: SupportedNetwork::Futurenet,
-        bounded_buffer_size: None,
-        staggered: None,
+        ..Default::default()
     };
 
-    let mut captive_core = CaptiveCore::new(config);
+    let mut captive_core = CaptiveCore::new(config).unwrap();
 
     // preparing just 10000 ledgers for simplicity.
     let range = Range::Bounded(BoundedRange(292_000, 302_000));
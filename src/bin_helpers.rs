@@ -0,0 +1,11 @@
+//! Helpers for binaries built on top of rs-ingest.
+
+/// Installs a global `tracing` subscriber printing events at `INFO`
+/// level and above to stdout.
+///
+/// Does nothing if a global subscriber was already installed.
+pub fn setup_default_tracing() {
+    let _ = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        .try_init();
+}
@@ -1,14 +1,34 @@
-use std::io::{self, Read};
-use std::sync::mpsc::{SendError, Sender, SyncSender};
+use async_trait::async_trait;
+use std::io::{self, BufRead, Read};
+use std::sync::mpsc::{RecvTimeoutError, SendError, Sender, SyncSender};
 use std::sync::{Arc, Mutex};
-use stellar_xdr::next::{LedgerCloseMeta, Limits, Type, TypeVariant};
+use std::thread;
+use std::time::{Duration, Instant};
+use stellar_xdr::next::{Frame, LedgerCloseMeta, Limits, ReadXdr, Type, WriteXdr};
+
+use crate::{logging, HeaderReader, IngestionMetrics, LedgerCloseMetaReader};
 
 /// prevents stack overflow
 pub const DEFAULT_XDR_RW_DEPTH_LIMIT: u32 = 500;
 
 // from the stellar/go/ingestion lib
-const META_PIPE_BUFFER_SIZE: usize = 10 * 1024 * 1024;
-const LEDGER_READ_AHEAD_BUFFER_SIZE: usize = 20;
+/// Default capacity in bytes of the buffer wrapping the stellar-core meta pipe.
+///
+/// The buffer is allocated once per reader, so this is a fixed 10 MiB cost.
+/// Pubnet ledger close metas are usually in the 1-5 MiB range, so the default
+/// can hold one or more full frames. On memory-constrained systems a smaller
+/// buffer (e.g. 1 MiB) still works but results in more reads from the pipe,
+/// while high-throughput pubnet replays may benefit from 32-64 MiB.
+pub const META_PIPE_BUFFER_SIZE: usize = 10 * 1024 * 1024;
+
+/// Default initial capacity (in ledgers) of the cached `Vec` used in single-thread mode.
+///
+/// This only pre-sizes the vector: the cache still grows to hold every
+/// prepared ledger. Since each entry is a decoded `LedgerCloseMeta`, a
+/// pubnet catchup of 10 000 ledgers can easily take several GiB regardless
+/// of this value, so set it close to the expected amount of ledgers to
+/// avoid repeated reallocations.
+pub const LEDGER_READ_AHEAD_BUFFER_SIZE: usize = 20;
 
 /// Enum to represent different types of errors related to `BufReader` operations.
 #[derive(thiserror::Error, Debug, Clone)]
@@ -21,6 +41,24 @@ pub enum BufReaderError {
     #[error("Error decoding XDR")]
     ReadXdrNext,
 
+    /// Error encountered while decoding XDR data, with the offset in the
+    /// pipe of the frame that failed to decode and up to its first
+    /// [`DECODE_CONTEXT_SAMPLE_SIZE`] bytes.
+    ///
+    /// Only returned with the `xdr-debug` feature, `ReadXdrNext` is
+    /// returned otherwise.
+    #[error("Error decoding XDR frame at offset {offset}: {raw_bytes:02x?}")]
+    XdrDecodeContext { offset: u64, raw_bytes: Vec<u8> },
+
+    /// IO error encountered while reading from the pipe, e.g. a broken pipe
+    /// or a frame cut short by the end of the stream.
+    #[error("IO error while reading from the pipe: {0}")]
+    StdioError(Arc<io::Error>),
+
+    /// Error encountered while encoding XDR data.
+    #[error("Error encoding XDR")]
+    WriteXdrNext,
+
     /// Attempted to run single-threaded mode with a specified transmitter, which is unused.
     #[error("Wants to run single-threaded mode but specified transmitter")]
     UnusedTransmitter,
@@ -47,10 +85,178 @@ pub enum BufReaderError {
 
     #[error("Failed to aquire lock")]
     LockError,
+
+    /// No ledger close meta frame was read from the pipe within the configured read timeout.
+    #[error("Timed out while waiting for ledger meta from the pipe")]
+    ReadTimeout,
+
+    /// The catchup feeding the pipe still exited unsuccessfully after being
+    /// retried the given amount of times. Sent as the last result of the
+    /// receiver.
+    #[error("Catchup failed after {retries} retries, last exit code {exit_code}")]
+    MaxRetriesExceeded { retries: u32, exit_code: i32 },
+}
+
+impl BufReaderError {
+    fn from_xdr(error: stellar_xdr::next::Error) -> Self {
+        match error {
+            stellar_xdr::next::Error::Io(io_error)
+                if io_error.kind() == io::ErrorKind::TimedOut =>
+            {
+                Self::ReadTimeout
+            }
+            stellar_xdr::next::Error::Io(io_error) => Self::StdioError(Arc::new(io_error)),
+            _ => Self::ReadXdrNext,
+        }
+    }
+}
+
+/// Maximum amount of leading bytes of a frame that failed to decode
+/// kept in `BufReaderError::XdrDecodeContext`.
+pub const DECODE_CONTEXT_SAMPLE_SIZE: usize = 128;
+
+/// Offset in the pipe and leading bytes of the frame being decoded.
+#[derive(Default)]
+struct DecodeContext {
+    offset: u64,
+    frame_offset: u64,
+    raw_bytes: Vec<u8>,
+}
+
+impl DecodeContext {
+    fn start_frame(&mut self) {
+        self.frame_offset = self.offset;
+        self.raw_bytes.clear();
+    }
+
+    fn record(&mut self, bytes: &[u8]) {
+        self.offset += bytes.len() as u64;
+
+        let sampled = std::cmp::min(
+            bytes.len(),
+            DECODE_CONTEXT_SAMPLE_SIZE.saturating_sub(self.raw_bytes.len()),
+        );
+        self.raw_bytes.extend_from_slice(&bytes[..sampled]);
+    }
+
+    fn to_error(&self) -> BufReaderError {
+        BufReaderError::XdrDecodeContext {
+            offset: self.frame_offset,
+            raw_bytes: self.raw_bytes.clone(),
+        }
+    }
+}
+
+/// Reader that records the bytes consumed by the XDR decoder in the
+/// decode context, if any.
+struct DecodeContextReader<'a, R> {
+    inner: R,
+    context: Option<&'a mut DecodeContext>,
+}
+
+impl<R: Read> Read for DecodeContextReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if let Some(context) = self.context.as_mut() {
+            context.record(&buf[..n]);
+        }
+
+        Ok(n)
+    }
+}
+
+// size of the chunks read by the timeout reader's background thread.
+const TIMEOUT_READER_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Reader that fails with `io::ErrorKind::TimedOut` when no data is
+/// available before the shared deadline.
+///
+/// The inner reader is consumed by a background thread that forwards
+/// chunks through a channel, since blocking pipe reads can't be
+/// interrupted otherwise. The thread exits at the end of the pipe or on
+/// a read error. If this reader is dropped while the pipe is stalled, the
+/// thread stays blocked until the pipe yields data, an error or EOF, e.g.
+/// once the stellar-core process is killed.
+struct TimeoutReader {
+    receiver: std::sync::mpsc::Receiver<io::Result<Vec<u8>>>,
+    chunk: Vec<u8>,
+    position: usize,
+    deadline: Arc<Mutex<Instant>>,
+}
+
+impl TimeoutReader {
+    fn new(mut inner: Box<dyn Read + Send>, deadline: Arc<Mutex<Instant>>) -> Self {
+        let (transmitter, receiver) = std::sync::mpsc::sync_channel(1);
+
+        thread::spawn(move || loop {
+            let mut chunk = vec![0; TIMEOUT_READER_CHUNK_SIZE];
+            let read = inner.read(&mut chunk).map(|n| {
+                chunk.truncate(n);
+                chunk
+            });
+            let done = !matches!(&read, Ok(chunk) if !chunk.is_empty());
+
+            if transmitter.send(read).is_err() || done {
+                break;
+            }
+        });
+
+        Self {
+            receiver,
+            chunk: Vec::new(),
+            position: 0,
+            deadline,
+        }
+    }
+}
+
+/// Reader that records the amount of bytes read in the ingestion metrics.
+struct CountingReader {
+    inner: Box<dyn Read + Send>,
+    metrics: Arc<IngestionMetrics>,
+}
+
+impl Read for CountingReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.metrics.record_bytes(n);
+
+        Ok(n)
+    }
+}
+
+impl Read for TimeoutReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position == self.chunk.len() {
+            let deadline = *self
+                .deadline
+                .lock()
+                .map_err(|_| io::Error::other("failed to aquire lock"))?;
+
+            match self
+                .receiver
+                .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            {
+                Ok(Ok(chunk)) => {
+                    self.chunk = chunk;
+                    self.position = 0;
+                }
+                Ok(Err(error)) => return Err(error),
+                Err(RecvTimeoutError::Timeout) => return Err(io::ErrorKind::TimedOut.into()),
+                Err(RecvTimeoutError::Disconnected) => return Ok(0),
+            }
+        }
+
+        let n = std::cmp::min(buf.len(), self.chunk.len() - self.position);
+        buf[..n].copy_from_slice(&self.chunk[self.position..self.position + n]);
+        self.position += n;
+
+        Ok(n)
+    }
 }
 
 /// Wrapper struct to hold the `LedgerCloseMeta` data.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct LedgerCloseMetaWrapper {
     /// The ledger close metadata associated with this wrapper.
     pub ledger_close_meta: LedgerCloseMeta,
@@ -62,6 +268,37 @@ impl LedgerCloseMetaWrapper {
             ledger_close_meta: inner,
         }
     }
+
+    /// Encodes the ledger close meta to raw XDR bytes.
+    pub fn to_xdr_bytes(&self) -> Result<Vec<u8>, BufReaderError> {
+        self.ledger_close_meta
+            .to_xdr(Limits::none())
+            .map_err(|_| BufReaderError::WriteXdrNext)
+    }
+
+    /// Decodes a ledger close meta from raw XDR bytes, as
+    /// produced by `to_xdr_bytes`.
+    pub fn from_xdr_bytes(bytes: &[u8]) -> Result<Self, BufReaderError> {
+        LedgerCloseMeta::from_xdr(bytes, Limits::depth(DEFAULT_XDR_RW_DEPTH_LIMIT))
+            .map(Self::new)
+            .map_err(|_| BufReaderError::ReadXdrNext)
+    }
+
+    /// Encodes the ledger close meta to a base64 XDR string,
+    /// e.g. to store or transmit it.
+    pub fn to_xdr_base64(&self) -> Result<String, BufReaderError> {
+        self.ledger_close_meta
+            .to_xdr_base64(Limits::none())
+            .map_err(|_| BufReaderError::WriteXdrNext)
+    }
+
+    /// Decodes a ledger close meta from a base64 XDR string, as
+    /// produced by `to_xdr_base64`.
+    pub fn from_xdr_base64(s: &str) -> Result<Self, BufReaderError> {
+        LedgerCloseMeta::from_xdr_base64(s, Limits::depth(DEFAULT_XDR_RW_DEPTH_LIMIT))
+            .map(Self::new)
+            .map_err(|_| BufReaderError::ReadXdrNext)
+    }
 }
 
 impl From<Type> for LedgerCloseMetaWrapper {
@@ -80,6 +317,9 @@ impl From<Type> for LedgerCloseMetaWrapper {
     }
 }
 
+// borrowed by `MetaResult::ledger_close_meta_ref` for end sentinels.
+static END_SENTINEL_ERROR: BufReaderError = BufReaderError::ReadXdrNext;
+
 /// Represents the result of processing ledger metadata.
 #[derive(Clone, Debug)]
 pub struct MetaResult {
@@ -90,6 +330,62 @@ pub struct MetaResult {
     pub err: Option<BufReaderError>,
 }
 
+impl MetaResult {
+    /// Returns the sequence of the ledger held by this result, or `None` if
+    /// the result is an error.
+    pub fn ledger_sequence(&self) -> Option<u32> {
+        self.ledger_close_meta
+            .as_ref()
+            .map(|wrapper| HeaderReader::sequence(&wrapper.ledger_close_meta))
+    }
+
+    /// Takes the ledger out of this result without cloning it.
+    ///
+    /// # Returns
+    ///
+    /// Returns the ledger, or the error of the result if it has no ledger.
+    /// End sentinels, which hold neither, yield `BufReaderError::ReadXdrNext`.
+    pub fn into_ledger_close_meta(self) -> Result<LedgerCloseMeta, BufReaderError> {
+        match self.ledger_close_meta {
+            Some(wrapper) => Ok(wrapper.ledger_close_meta),
+            None => Err(self.err.unwrap_or(BufReaderError::ReadXdrNext)),
+        }
+    }
+
+    /// Borrows the ledger of this result.
+    ///
+    /// # Returns
+    ///
+    /// Returns the ledger, or the error of the result if it has no ledger.
+    /// End sentinels, which hold neither, yield `BufReaderError::ReadXdrNext`.
+    pub fn ledger_close_meta_ref(&self) -> Result<&LedgerCloseMeta, &BufReaderError> {
+        match (&self.ledger_close_meta, &self.err) {
+            (Some(wrapper), _) => Ok(&wrapper.ledger_close_meta),
+            (None, Some(error)) => Err(error),
+            (None, None) => Err(&END_SENTINEL_ERROR),
+        }
+    }
+
+    /// Returns whether this result marks the end of the stream rather than a
+    /// failure, which happens when the stellar-core pipe is closed once the
+    /// catchup job finishes.
+    ///
+    /// Results without a ledger are end sentinels if they carry no error or
+    /// an unexpected EOF `StdioError`. A `ReadXdrNext` error is a frame that
+    /// failed to decode, not the end of the stream.
+    pub fn is_catchup_end_sentinel(&self) -> bool {
+        if self.ledger_close_meta.is_some() {
+            return false;
+        }
+
+        match &self.err {
+            None => true,
+            Some(BufReaderError::StdioError(error)) => error.kind() == io::ErrorKind::UnexpectedEof,
+            Some(_) => false,
+        }
+    }
+}
+
 /// Enum to indicate the mode of operation for `BufferedLedgerMetaReader`.
 #[derive(PartialEq, Eq, Clone)]
 pub enum BufferedLedgerMetaReaderMode {
@@ -115,6 +411,23 @@ pub struct BufferedLedgerMetaReader {
     /// This will only be used when running offline.
     cached: Option<Arc<Mutex<Vec<MetaResult>>>>,
 
+    /// Initial capacity of the cached vector.
+    read_ahead_buffer_size: usize,
+
+    /// Optional maximum time to wait for a single ledger meta frame.
+    read_timeout: Option<Duration>,
+
+    /// Deadline for the frame currently being read, shared with the
+    /// timeout reader. Only set when `read_timeout` is.
+    read_deadline: Option<Arc<Mutex<Instant>>>,
+
+    /// Optional metrics updated on every frame read.
+    metrics: Option<Arc<IngestionMetrics>>,
+
+    /// Context of the frame being decoded, only kept with
+    /// the `xdr-debug` feature.
+    decode_context: Option<DecodeContext>,
+
     /// An optional transmitter for sending metadata results.
     /// This will only be used when running online
     transmitter: Option<Sender<Box<MetaResult>>>,
@@ -139,6 +452,11 @@ impl Clone for BufferedLedgerMetaReader {
             mode: self.mode.clone(),
             reader: None,
             cached: None,
+            read_ahead_buffer_size: self.read_ahead_buffer_size,
+            read_timeout: self.read_timeout,
+            read_deadline: None,
+            metrics: self.metrics.clone(),
+            decode_context: None,
             transmitter: None,
             sync_transmitter: None,
             async_transmitter: None,
@@ -156,10 +474,13 @@ impl BufferedLedgerMetaReader {
     /// * `mode` - The mode of operation for the reader.
     /// * `reader` - The boxed reader used for reading data.
     /// * `transmitter` - An optional transmitter for sending metadata results in multi-thread mode.
+    /// * `pipe_buffer_size` - Optional capacity in bytes of the pipe buffer, defaults to [`META_PIPE_BUFFER_SIZE`].
+    /// * `read_ahead_buffer_size` - Optional initial capacity of the single-thread cache, defaults to [`LEDGER_READ_AHEAD_BUFFER_SIZE`].
     ///
     /// # Returns
     ///
     /// Returns a new `BufferedLedgerMetaReader` instance if successful, or a `BufReaderError` if an issue occurs.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         mode: BufferedLedgerMetaReaderMode,
         reader: Box<dyn Read + Send>,
@@ -167,8 +488,13 @@ impl BufferedLedgerMetaReader {
         sync_transmitter: Option<SyncSender<Box<MetaResult>>>,
         async_transmitter: Option<tokio::sync::mpsc::UnboundedSender<Box<MetaResult>>>,
         async_transmitter_bounded: Option<tokio::sync::mpsc::Sender<Box<MetaResult>>>,
+        pipe_buffer_size: Option<usize>,
+        read_ahead_buffer_size: Option<usize>,
     ) -> Result<Self, BufReaderError> {
-        let reader = io::BufReader::with_capacity(META_PIPE_BUFFER_SIZE, reader);
+        let reader =
+            io::BufReader::with_capacity(pipe_buffer_size.unwrap_or(META_PIPE_BUFFER_SIZE), reader);
+        let read_ahead_buffer_size =
+            read_ahead_buffer_size.unwrap_or(LEDGER_READ_AHEAD_BUFFER_SIZE);
 
         // perform some safety checks and assing
         // chached.
@@ -190,14 +516,18 @@ impl BufferedLedgerMetaReader {
                     }
 
                     Some(Arc::new(Mutex::new(Vec::with_capacity(
-                        LEDGER_READ_AHEAD_BUFFER_SIZE,
+                        read_ahead_buffer_size,
                     ))))
                 }
 
                 BufferedLedgerMetaReaderMode::MultiThread => {
                     // make sure that at least one transmittor is some
                     // when running multi-thread mode.
-                    if !tx_is && !sync_tx_is && !async_transmitter.is_some() {
+                    if !tx_is
+                        && !sync_tx_is
+                        && async_transmitter.is_none()
+                        && async_transmitter_bounded.is_none()
+                    {
                         return Err(BufReaderError::MissingTransmitter);
                     }
                     None
@@ -209,6 +539,11 @@ impl BufferedLedgerMetaReader {
             mode,
             reader: Some(reader),
             cached,
+            read_ahead_buffer_size,
+            read_timeout: None,
+            read_deadline: None,
+            metrics: None,
+            decode_context: cfg!(feature = "xdr-debug").then(DecodeContext::default),
             transmitter,
             sync_transmitter,
             async_transmitter,
@@ -217,6 +552,128 @@ impl BufferedLedgerMetaReader {
         })
     }
 
+    /// Sets a timeout for reading each ledger meta frame from the pipe.
+    ///
+    /// The timeout is reset on every frame read, so it only triggers when
+    /// stellar-core stalls (e.g. waiting on archive downloads) rather than
+    /// acting as a wall-clock limit. On timeout the reader emits a `MetaResult`
+    /// with `BufReaderError::ReadTimeout` and stops reading, leaving it to the
+    /// caller to either restart the runner or log and continue.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - The maximum time to wait for a single frame.
+    ///
+    /// # Returns
+    ///
+    /// Returns the `BufferedLedgerMetaReader` reading with the given timeout.
+    pub fn with_read_timeout(mut self, timeout: Duration) -> Self {
+        let deadline = Arc::new(Mutex::new(Instant::now() + timeout));
+
+        // readers are only wrapped right after creation, so
+        // nothing is buffered yet and no data is lost.
+        if let Some(reader) = self.reader.take() {
+            let capacity = reader.capacity();
+            let inner = reader.into_inner();
+
+            self.reader = Some(io::BufReader::with_capacity(
+                capacity,
+                Box::new(TimeoutReader::new(inner, deadline.clone())),
+            ));
+        }
+
+        self.read_timeout = Some(timeout);
+        self.read_deadline = Some(deadline);
+        self
+    }
+
+    /// Sets the metrics to update while reading from the pipe.
+    ///
+    /// # Arguments
+    ///
+    /// * `metrics` - The shared metrics to record read bytes and decoded frames in.
+    ///
+    /// # Returns
+    ///
+    /// Returns the `BufferedLedgerMetaReader` recording its progress in `metrics`.
+    pub fn with_metrics(mut self, metrics: Arc<IngestionMetrics>) -> Self {
+        // see `with_read_timeout` on re-wrapping the reader.
+        if let Some(reader) = self.reader.take() {
+            let capacity = reader.capacity();
+            let inner = reader.into_inner();
+
+            self.reader = Some(io::BufReader::with_capacity(
+                capacity,
+                Box::new(CountingReader {
+                    inner,
+                    metrics: metrics.clone(),
+                }),
+            ));
+        }
+
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Retrieves the configured read timeout, if any.
+    pub fn read_timeout(&self) -> Option<Duration> {
+        self.read_timeout
+    }
+
+    fn reset_read_deadline(
+        read_timeout: Option<Duration>,
+        read_deadline: &Option<Arc<Mutex<Instant>>>,
+    ) -> Result<(), BufReaderError> {
+        if let (Some(timeout), Some(deadline)) = (read_timeout, read_deadline) {
+            *deadline.lock().map_err(|_| BufReaderError::LockError)? = Instant::now() + timeout;
+        }
+
+        Ok(())
+    }
+
+    /// Reads the next ledger close meta frame from `reader`.
+    ///
+    /// # Returns
+    ///
+    /// Returns `None` once the pipe is exhausted, the decoded ledger
+    /// close meta or the error encountered while decoding it otherwise.
+    fn read_frame(
+        reader: &mut io::BufReader<Box<dyn Read + Send>>,
+        decode_context: &mut Option<DecodeContext>,
+    ) -> Option<Result<LedgerCloseMeta, BufReaderError>> {
+        // peek the buffer to tell a clean EOF apart from a truncated frame.
+        match reader.fill_buf() {
+            Ok([]) => return None,
+            Ok(_) => (),
+            Err(error) => {
+                return Some(Err(BufReaderError::from_xdr(stellar_xdr::next::Error::Io(
+                    error,
+                ))))
+            }
+        }
+
+        if let Some(context) = decode_context.as_mut() {
+            context.start_frame();
+        }
+
+        let mut xdr_reader = stellar_xdr::next::Limited::new(
+            DecodeContextReader {
+                inner: reader,
+                context: decode_context.as_mut(),
+            },
+            Limits::depth(DEFAULT_XDR_RW_DEPTH_LIMIT),
+        );
+        let frame = Frame::<LedgerCloseMeta>::read_xdr(&mut xdr_reader);
+
+        Some(match frame {
+            Ok(Frame(ledger_close_meta)) => Ok(ledger_close_meta),
+            Err(error) => match (BufReaderError::from_xdr(error), decode_context.as_ref()) {
+                (BufReaderError::ReadXdrNext, Some(context)) => Err(context.to_error()),
+                (error, _) => Err(error),
+            },
+        })
+    }
+
     /// Retrieves the thread mode of the `BufferedLedgerMetaReader`.
     ///
     /// # Returns
@@ -228,6 +685,7 @@ impl BufferedLedgerMetaReader {
 }
 
 /// Trait for reading ledger metadata in single-thread mode from a buffered source.
+#[async_trait]
 pub trait SingleThreadBufferedLedgerMetaReader {
     /// Reads ledger metadata from the buffered source in single-thread mode.
     ///
@@ -236,6 +694,27 @@ pub trait SingleThreadBufferedLedgerMetaReader {
     /// Returns `Ok(())` if reading is successful, or a `BufReaderError` if an issue occurs.
     fn single_thread_read_ledger_meta_from_pipe(&mut self) -> Result<(), BufReaderError>;
 
+    /// Async version of `single_thread_read_ledger_meta_from_pipe`.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if reading is successful, or a `BufReaderError` if an issue occurs.
+    async fn async_single_thread_read_ledger_meta_from_pipe(
+        &mut self,
+    ) -> Result<(), BufReaderError>;
+
+    /// Reads ledger metadata from the buffered source in single-thread mode,
+    /// calling `on_ledger` with the sequence of every ledger as it is decoded
+    /// and before it is cached.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if reading is successful, or a `BufReaderError` if an issue occurs.
+    fn single_thread_read_ledger_meta_from_pipe_with_progress(
+        &mut self,
+        on_ledger: &mut dyn FnMut(u32),
+    ) -> Result<(), BufReaderError>;
+
     /// Reads and retrieves cached ledger metadata in single-thread mode.
     ///
     /// # Returns
@@ -243,6 +722,15 @@ pub trait SingleThreadBufferedLedgerMetaReader {
     /// Returns a vector of `MetaResult` if retrieval is successful, or a `BufReaderError` if an issue occurs.
     fn read_meta(&self) -> Result<Vec<MetaResult>, BufReaderError>;
 
+    /// Moves the cached ledger metadata out of the reader in single-thread mode,
+    /// leaving the cache empty. Unlike [`Self::read_meta`] this doesn't clone
+    /// the cached entries.
+    ///
+    /// # Returns
+    ///
+    /// Returns a vector of `MetaResult` if retrieval is successful, or a `BufReaderError` if an issue occurs.
+    fn drain(&mut self) -> Result<Vec<MetaResult>, BufReaderError>;
+
     /// Clears the cached buffered ledger metadata in single-thread mode.
     ///
     /// # Returns
@@ -252,6 +740,7 @@ pub trait SingleThreadBufferedLedgerMetaReader {
 }
 
 /// Trait for reading ledger metadata in multi-thread mode from a buffered source.
+#[async_trait]
 pub trait MultiThreadBufferedLedgerMetaReader {
     /// Reads ledger metadata from the buffered source in multi-thread mode.
     ///
@@ -259,10 +748,34 @@ pub trait MultiThreadBufferedLedgerMetaReader {
     ///
     /// Returns `Ok(())` if reading is successful, or a `BufReaderError` if an issue occurs.
     fn multi_thread_read_ledger_meta_from_pipe(&mut self) -> Result<(), BufReaderError>;
+
+    /// Async version of `multi_thread_read_ledger_meta_from_pipe`, sending the
+    /// metadata through the async transmitters.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if reading is successful, or a `BufReaderError` if an issue occurs.
+    async fn async_multi_thread_read_ledger_meta_from_pipe(&mut self)
+        -> Result<(), BufReaderError>;
 }
 
+#[async_trait]
 impl SingleThreadBufferedLedgerMetaReader for BufferedLedgerMetaReader {
     fn single_thread_read_ledger_meta_from_pipe(&mut self) -> Result<(), BufReaderError> {
+        self.single_thread_read_ledger_meta_from_pipe_with_progress(&mut |_| ())
+    }
+
+    // results are cached rather than sent, so there is nothing to await.
+    async fn async_single_thread_read_ledger_meta_from_pipe(
+        &mut self,
+    ) -> Result<(), BufReaderError> {
+        self.single_thread_read_ledger_meta_from_pipe()
+    }
+
+    fn single_thread_read_ledger_meta_from_pipe_with_progress(
+        &mut self,
+        on_ledger: &mut dyn FnMut(u32),
+    ) -> Result<(), BufReaderError> {
         if self.mode != BufferedLedgerMetaReaderMode::SingleThread {
             return Err(BufReaderError::WrongModeMultiThread);
         }
@@ -271,24 +784,34 @@ impl SingleThreadBufferedLedgerMetaReader for BufferedLedgerMetaReader {
             return Err(BufReaderError::UsedClonedBufreader);
         }
 
-        let mut reader = self.reader.as_mut().unwrap();
-        let mut xdr_reader =
-            stellar_xdr::next::Limited::new(&mut reader, Limits::depth(DEFAULT_XDR_RW_DEPTH_LIMIT));
-        for t in stellar_xdr::next::Type::read_xdr_framed_iter(
-            TypeVariant::LedgerCloseMeta,
-            &mut xdr_reader,
-        ) {
+        Self::reset_read_deadline(self.read_timeout, &self.read_deadline)?;
+
+        let reader = self.reader.as_mut().unwrap();
+        while let Some(t) = Self::read_frame(reader, &mut self.decode_context) {
             let meta_obj = match t {
                 Ok(ledger_close_meta) => MetaResult {
-                    ledger_close_meta: Some(ledger_close_meta.into()),
+                    ledger_close_meta: Some(LedgerCloseMetaWrapper::new(ledger_close_meta)),
                     err: None,
                 },
 
-                Err(_) => MetaResult {
+                Err(error) => MetaResult {
                     ledger_close_meta: None,
-                    err: Some(BufReaderError::ReadXdrNext),
+                    err: Some(error),
                 },
             };
+            let timed_out = matches!(meta_obj.err, Some(BufReaderError::ReadTimeout));
+
+            if let Some(metrics) = self.metrics.as_ref() {
+                metrics.record_result(&meta_obj);
+            }
+
+            #[cfg(feature = "tracing")]
+            let _span = ledger_span(&meta_obj, self.metrics.as_deref()).entered();
+            logging::trace!("Read ledger close meta from pipe");
+
+            if let Ok(sequence) = LedgerCloseMetaReader::ledger_sequence(&meta_obj) {
+                on_ledger(sequence);
+            }
 
             // The below unwrap on cached is safe since initialization
             // prevents initializing in the wrong mode and all
@@ -299,6 +822,13 @@ impl SingleThreadBufferedLedgerMetaReader for BufferedLedgerMetaReader {
                 .lock()
                 .map_err(|_| BufReaderError::LockError)?
                 .push(meta_obj);
+
+            if timed_out {
+                logging::warning!("Timed out while reading ledger meta from the pipe");
+                break;
+            }
+
+            Self::reset_read_deadline(self.read_timeout, &self.read_deadline)?;
         }
 
         Ok(())
@@ -325,6 +855,33 @@ impl SingleThreadBufferedLedgerMetaReader for BufferedLedgerMetaReader {
         Ok((*locked).clone())
     }
 
+    fn drain(&mut self) -> Result<Vec<MetaResult>, BufReaderError> {
+        if self.mode != BufferedLedgerMetaReaderMode::SingleThread {
+            return Err(BufReaderError::WrongModeMultiThread);
+        }
+
+        if self.cloned {
+            return Err(BufReaderError::UsedClonedBufreader);
+        }
+
+        // The below unwrap on cached is safe since initialization
+        // prevents initializing in the wrong mode and all
+        // BufferedLedgerMetaReader fields are private.
+        let cached = self.cached.replace(Arc::new(Mutex::new(Vec::with_capacity(
+            self.read_ahead_buffer_size,
+        ))));
+
+        // clones of the reader don't share the cache, but if the `Arc` still
+        // has other owners the entries are cloned rather than taken from them.
+        match Arc::try_unwrap(cached.unwrap()) {
+            Ok(mutex) => mutex.into_inner().map_err(|_| BufReaderError::LockError),
+            Err(shared) => {
+                let locked = shared.lock().map_err(|_| BufReaderError::LockError)?;
+                Ok((*locked).clone())
+            }
+        }
+    }
+
     fn clear_buffered(&mut self) -> Result<(), BufReaderError> {
         if self.mode != BufferedLedgerMetaReaderMode::SingleThread {
             return Err(BufReaderError::WrongModeMultiThread);
@@ -335,12 +892,13 @@ impl SingleThreadBufferedLedgerMetaReader for BufferedLedgerMetaReader {
         }
 
         self.cached = Some(Arc::new(Mutex::new(Vec::with_capacity(
-            LEDGER_READ_AHEAD_BUFFER_SIZE,
+            self.read_ahead_buffer_size,
         ))));
         Ok(())
     }
 }
 
+#[async_trait]
 impl MultiThreadBufferedLedgerMetaReader for BufferedLedgerMetaReader {
     fn multi_thread_read_ledger_meta_from_pipe(&mut self) -> Result<(), BufReaderError> {
         if self.mode != BufferedLedgerMetaReaderMode::MultiThread {
@@ -351,24 +909,30 @@ impl MultiThreadBufferedLedgerMetaReader for BufferedLedgerMetaReader {
             return Err(BufReaderError::UsedClonedBufreader);
         }
 
-        let mut reader = self.reader.as_mut().unwrap();
-        let mut xdr_reader =
-            stellar_xdr::next::Limited::new(&mut reader, Limits::depth(DEFAULT_XDR_RW_DEPTH_LIMIT));
-        for t in stellar_xdr::next::Type::read_xdr_framed_iter(
-            TypeVariant::LedgerCloseMeta,
-            &mut xdr_reader,
-        ) {
+        Self::reset_read_deadline(self.read_timeout, &self.read_deadline)?;
+
+        let reader = self.reader.as_mut().unwrap();
+        while let Some(t) = Self::read_frame(reader, &mut self.decode_context) {
             let meta_obj = match t {
                 Ok(ledger_close_meta) => MetaResult {
-                    ledger_close_meta: Some(ledger_close_meta.into()),
+                    ledger_close_meta: Some(LedgerCloseMetaWrapper::new(ledger_close_meta)),
                     err: None,
                 },
 
-                Err(_) => MetaResult {
+                Err(error) => MetaResult {
                     ledger_close_meta: None,
-                    err: Some(BufReaderError::ReadXdrNext),
+                    err: Some(error),
                 },
             };
+            let timed_out = matches!(meta_obj.err, Some(BufReaderError::ReadTimeout));
+
+            if let Some(metrics) = self.metrics.as_ref() {
+                metrics.record_result(&meta_obj);
+            }
+
+            #[cfg(feature = "tracing")]
+            let _span = ledger_span(&meta_obj, self.metrics.as_deref()).entered();
+            logging::trace!("Read ledger close meta from pipe");
 
             if let Some(tx) = self.sync_transmitter.as_ref() {
                 tx.send(Box::new(meta_obj))?
@@ -382,14 +946,19 @@ impl MultiThreadBufferedLedgerMetaReader for BufferedLedgerMetaReader {
                     .unwrap()
                     .send(Box::new(meta_obj))?
             }
+
+            if timed_out {
+                logging::warning!("Timed out while reading ledger meta from the pipe");
+                break;
+            }
+
+            Self::reset_read_deadline(self.read_timeout, &self.read_deadline)?;
         }
 
         Ok(())
     }
-}
 
-impl BufferedLedgerMetaReader {
-    pub async fn async_multi_thread_read_ledger_meta_from_pipe(
+    async fn async_multi_thread_read_ledger_meta_from_pipe(
         &mut self,
     ) -> Result<(), BufReaderError> {
         if self.mode != BufferedLedgerMetaReaderMode::MultiThread {
@@ -400,40 +969,327 @@ impl BufferedLedgerMetaReader {
             return Err(BufReaderError::UsedClonedBufreader);
         }
 
-        let mut reader = self.reader.as_mut().unwrap();
-        let mut xdr_reader =
-            stellar_xdr::next::Limited::new(&mut reader, Limits::depth(DEFAULT_XDR_RW_DEPTH_LIMIT));
-        for t in stellar_xdr::next::Type::read_xdr_framed_iter(
-            TypeVariant::LedgerCloseMeta,
-            &mut xdr_reader,
-        ) {
-            println!("\n\n Inner got result");
+        Self::reset_read_deadline(self.read_timeout, &self.read_deadline)?;
+
+        let reader = self.reader.as_mut().unwrap();
+        while let Some(t) = Self::read_frame(reader, &mut self.decode_context) {
             let meta_obj = match t {
                 Ok(ledger_close_meta) => MetaResult {
-                    ledger_close_meta: Some(ledger_close_meta.into()),
+                    ledger_close_meta: Some(LedgerCloseMetaWrapper::new(ledger_close_meta)),
                     err: None,
                 },
 
-                Err(_) => MetaResult {
+                Err(error) => MetaResult {
                     ledger_close_meta: None,
-                    err: Some(BufReaderError::ReadXdrNext),
+                    err: Some(error),
                 },
             };
+            let timed_out = matches!(meta_obj.err, Some(BufReaderError::ReadTimeout));
+
+            if let Some(metrics) = self.metrics.as_ref() {
+                metrics.record_result(&meta_obj);
+            }
+
+            // the span guard isn't `Send`, so it must be dropped before awaiting.
+            {
+                #[cfg(feature = "tracing")]
+                let _span = ledger_span(&meta_obj, self.metrics.as_deref()).entered();
+                logging::trace!("Read ledger close meta from pipe");
+            }
 
             if let Some(tx) = self.async_transmitter.as_ref() {
                 let transmit = tx.send(Box::new(meta_obj));
 
                 if transmit.is_err() {
-                    log::error!(
+                    logging::error!(
                         "Failed to transmit ledger close: {:?}. Receiver dropped, shutting down ...",
                         transmit.err().unwrap()
                     );
 
                     panic!("Receiver dropped");
                 }
+            } else if let Some(tx) = self.async_transmitter_bounded.as_ref() {
+                // waits for the receiver to make room, applying back-pressure
+                // on the pipe.
+                tx.send(Box::new(meta_obj))
+                    .await
+                    .map_err(|error| SendError(error.0))?
+            }
+
+            if timed_out {
+                logging::warning!("Timed out while reading ledger meta from the pipe");
+                break;
             }
+
+            Self::reset_read_deadline(self.read_timeout, &self.read_deadline)?;
         }
 
         Ok(())
     }
 }
+
+/// Creates the `TRACE` span of a ledger read from the pipe.
+#[cfg(feature = "tracing")]
+fn ledger_span(meta_obj: &MetaResult, metrics: Option<&IngestionMetrics>) -> tracing::Span {
+    let span = tracing::trace_span!(
+        "ledger",
+        ledger_seq = tracing::field::Empty,
+        bytes_read = tracing::field::Empty
+    );
+
+    if let Ok(sequence) = LedgerCloseMetaReader::ledger_sequence(meta_obj) {
+        span.record("ledger_seq", sequence);
+    }
+
+    // bytes are only counted when metrics are set.
+    if let Some(metrics) = metrics {
+        span.record(
+            "bytes_read",
+            metrics
+                .bytes_read
+                .load(std::sync::atomic::Ordering::Relaxed),
+        );
+    }
+
+    span
+}
+
+/// Lazily reads ledger metadata from the pipe in single-thread mode.
+///
+/// Every call to `next()` decodes a single XDR frame, so ledgers can be
+/// processed as soon as stellar-core emits them without holding the whole
+/// batch in memory. This is the recommended way of consuming a single-thread
+/// reader, `read_meta` and `clear_buffered` are kept for the batch API.
+///
+/// The iterator ends when the pipe is exhausted. A decoding error or a read
+/// timeout (see `with_read_timeout`) is yielded as a `MetaResult` with `err`
+/// set and ends the iteration. Readers in multi-thread mode or cloned readers
+/// yield nothing.
+impl Iterator for BufferedLedgerMetaReader {
+    type Item = MetaResult;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.mode != BufferedLedgerMetaReaderMode::SingleThread || self.cloned {
+            return None;
+        }
+
+        if let Err(error) = Self::reset_read_deadline(self.read_timeout, &self.read_deadline) {
+            self.reader = None;
+            return Some(MetaResult {
+                ledger_close_meta: None,
+                err: Some(error),
+            });
+        }
+
+        let reader = self.reader.as_mut()?;
+        let meta_obj = match Self::read_frame(reader, &mut self.decode_context)? {
+            Ok(ledger_close_meta) => MetaResult {
+                ledger_close_meta: Some(LedgerCloseMetaWrapper::new(ledger_close_meta)),
+                err: None,
+            },
+
+            Err(error) => {
+                // the stream can't be resynced after a bad frame or a timeout.
+                self.reader = None;
+                MetaResult {
+                    ledger_close_meta: None,
+                    err: Some(error),
+                }
+            }
+        };
+
+        if let Some(metrics) = self.metrics.as_ref() {
+            metrics.record_result(&meta_obj);
+        }
+
+        Some(meta_obj)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use stellar_xdr::next::LedgerCloseMetaV0;
+
+    use super::*;
+    use crate::testing::encode_frames;
+
+    fn ledgers(sequences: std::ops::RangeInclusive<u32>) -> Vec<LedgerCloseMeta> {
+        sequences
+            .map(|sequence| {
+                let mut v0 = LedgerCloseMetaV0::default();
+                v0.ledger_header.header.ledger_seq = sequence;
+                LedgerCloseMeta::V0(v0)
+            })
+            .collect()
+    }
+
+    fn read_sequences(
+        pipe_buffer_size: Option<usize>,
+        read_ahead_buffer_size: Option<usize>,
+    ) -> Vec<Option<u32>> {
+        let frames = encode_frames(&ledgers(10..=20)).unwrap();
+        let mut reader = BufferedLedgerMetaReader::new(
+            BufferedLedgerMetaReaderMode::SingleThread,
+            Box::new(Cursor::new(frames)),
+            None,
+            None,
+            None,
+            None,
+            pipe_buffer_size,
+            read_ahead_buffer_size,
+        )
+        .unwrap();
+
+        reader.single_thread_read_ledger_meta_from_pipe().unwrap();
+        reader
+            .drain()
+            .unwrap()
+            .iter()
+            .map(MetaResult::ledger_sequence)
+            .collect()
+    }
+
+    #[test]
+    fn custom_capacity_reads_same_ledgers_as_default() {
+        let default = read_sequences(None, None);
+        assert_eq!(
+            default.iter().flatten().copied().collect::<Vec<_>>(),
+            (10..=20).collect::<Vec<_>>()
+        );
+
+        // a pipe buffer smaller than a single frame.
+        assert_eq!(read_sequences(Some(16), Some(1)), default);
+        assert_eq!(read_sequences(Some(1 << 20), Some(1024)), default);
+    }
+
+    #[test]
+    fn boxed_single_thread_reader_reports_progress() {
+        let mut reader: Box<dyn SingleThreadBufferedLedgerMetaReader> = Box::new(
+            BufferedLedgerMetaReader::new(
+                BufferedLedgerMetaReaderMode::SingleThread,
+                Box::new(Cursor::new(encode_frames(&ledgers(10..=12)).unwrap())),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap(),
+        );
+
+        let mut progress = Vec::new();
+        reader
+            .single_thread_read_ledger_meta_from_pipe_with_progress(&mut |seq| progress.push(seq))
+            .unwrap();
+
+        assert_eq!(progress, vec![10, 11, 12]);
+    }
+
+    #[test]
+    fn xdr_base64_round_trip() {
+        let wrapper = LedgerCloseMetaWrapper::new(ledgers(42..=42).remove(0));
+        let encoded = wrapper.to_xdr_base64().unwrap();
+
+        assert_eq!(
+            LedgerCloseMetaWrapper::from_xdr_base64(&encoded).unwrap(),
+            wrapper
+        );
+    }
+
+    #[test]
+    fn xdr_bytes_round_trip() {
+        let wrapper = LedgerCloseMetaWrapper::new(ledgers(42..=42).remove(0));
+        let encoded = wrapper.to_xdr_bytes().unwrap();
+
+        assert_eq!(
+            LedgerCloseMetaWrapper::from_xdr_bytes(&encoded).unwrap(),
+            wrapper
+        );
+    }
+
+    #[test]
+    fn decode_failure_is_not_an_end_sentinel() {
+        let mut frames = encode_frames(&ledgers(10..=11)).unwrap();
+        // a frame whose union discriminant matches no meta version.
+        frames.extend_from_slice(&(4u32 | 0x8000_0000).to_be_bytes());
+        frames.extend_from_slice(&u32::MAX.to_be_bytes());
+
+        let mut reader = BufferedLedgerMetaReader::new(
+            BufferedLedgerMetaReaderMode::SingleThread,
+            Box::new(Cursor::new(frames)),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let _ = reader.single_thread_read_ledger_meta_from_pipe();
+
+        let results = reader.drain().unwrap();
+        assert_eq!(results.len(), 3);
+        assert!(results[2].err.is_some());
+        assert!(!results[2].is_catchup_end_sentinel());
+    }
+
+    fn async_bounded_reader(
+        sequences: std::ops::RangeInclusive<u32>,
+        transmitter: tokio::sync::mpsc::Sender<Box<MetaResult>>,
+    ) -> BufferedLedgerMetaReader {
+        BufferedLedgerMetaReader::new(
+            BufferedLedgerMetaReaderMode::MultiThread,
+            Box::new(Cursor::new(encode_frames(&ledgers(sequences)).unwrap())),
+            None,
+            None,
+            None,
+            Some(transmitter),
+            None,
+            None,
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn bounded_async_channel_applies_back_pressure() {
+        let (transmitter, mut receiver) = tokio::sync::mpsc::channel(2);
+        let mut reader = async_bounded_reader(10..=20, transmitter);
+        let mut read = tokio::spawn(async move {
+            let result = reader.async_multi_thread_read_ledger_meta_from_pipe();
+            result.await
+        });
+
+        // the reader fills the channel and then waits for room.
+        let waiting = tokio::time::timeout(Duration::from_millis(100), &mut read).await;
+        assert!(waiting.is_err());
+        assert_eq!(receiver.len(), 2);
+
+        let mut sequences = Vec::new();
+        while let Some(result) = receiver.recv().await {
+            sequences.extend(result.ledger_sequence());
+        }
+
+        assert_eq!(sequences, (10..=20).collect::<Vec<_>>());
+        let finished = tokio::time::timeout(Duration::from_secs(1), read).await;
+        assert!(finished.unwrap().unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn bounded_async_channel_errors_when_receiver_dropped() {
+        let (transmitter, receiver) = tokio::sync::mpsc::channel(2);
+        drop(receiver);
+
+        let mut reader = async_bounded_reader(10..=20, transmitter);
+        let read = tokio::time::timeout(
+            Duration::from_secs(1),
+            reader.async_multi_thread_read_ledger_meta_from_pipe(),
+        )
+        .await
+        .unwrap();
+
+        assert!(matches!(read, Err(BufReaderError::SendError(_))));
+    }
+}
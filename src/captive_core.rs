@@ -1,16 +1,76 @@
 use crate::{
-    toml::generate_predefined_cfg, BufferedLedgerMetaReaderMode, IngestionConfig, MetaResult,
-    RunnerError, StellarCoreRunner, StellarCoreRunnerPublic,
+    filter_map_ledgers, logging,
+    toml::{generate_predefined_cfg, CatchupLimits},
+    AsyncMetaReceiver, BufReaderError, BufferedLedgerMetaReader, BufferedLedgerMetaReaderMode,
+    HeaderReader, IngestionConfig, IngestionMetrics, LedgerBatch, LedgerCloseMetaReader,
+    LedgerCloseMetaWrapper, LedgerStream, MetaResult, RunnerError, SorobanEventReader,
+    StellarCoreRunner, StellarCoreRunnerPublic, SupportedNetwork,
 };
-use std::sync::mpsc::Receiver;
-use stellar_xdr::next::LedgerCloseMeta;
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    fs::{self, File, OpenOptions},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{Receiver, Sender},
+        Arc,
+    },
+    thread,
+};
+use stellar_xdr::next::{ContractEvent, LedgerCloseMeta, Limits, WriteXdr};
+use tokio::sync::watch;
+
+/// Amount of ledgers in a history archive checkpoint. Checkpoint ledgers
+/// are the last ledger of each checkpoint, i.e. `63`, `127`, `191`, ...
+pub const CHECKPOINT_FREQUENCY: u32 = 64;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 /// Represents a bounded range
 pub struct BoundedRange(pub u32, pub u32);
 
+impl BoundedRange {
+    /// Checks whether the range can be caught up, i.e. it is not empty
+    /// and doesn't start before ledger 2, since ledger 1 is the genesis
+    /// ledger and can't be replayed.
+    pub fn is_valid(&self) -> bool {
+        self.0 <= self.1 && self.0 >= 2
+    }
+
+    /// Gets the amount of ledgers in the range, `0` if it's empty.
+    pub fn len(&self) -> u32 {
+        if self.is_empty() {
+            0
+        } else {
+            (self.1 - self.0).saturating_add(1)
+        }
+    }
+
+    /// Checks whether the range is empty, i.e. `from > to`.
+    pub fn is_empty(&self) -> bool {
+        self.0 > self.1
+    }
+
+    /// Checks whether the ledger `seq` is in the range.
+    pub fn contains(&self, seq: u32) -> bool {
+        seq >= self.0 && seq <= self.1
+    }
+
+    /// Checks whether the range has ledgers in common with `other`.
+    pub fn overlaps(&self, other: &BoundedRange) -> bool {
+        !self.is_empty() && !other.is_empty() && self.0 <= other.1 && other.0 <= self.1
+    }
+}
+
+impl std::fmt::Display for BoundedRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}..{}]", self.0, self.1)
+    }
+}
+
 /// Ranges supported.
 /// Currently unbounded ranges are not supported.
+#[derive(Clone, Debug)]
 pub enum Range {
     /// Bounded range
     Bounded(BoundedRange),
@@ -31,6 +91,59 @@ impl Range {
             Range::Bounded(bounded_range) => (bounded_range.0, bounded_range.1),
         }
     }
+
+    /// Widens `from..=to` to whole history archive checkpoints, from the
+    /// first ledger of the checkpoint containing `from` to the checkpoint
+    /// ledger of the checkpoint containing `to`.
+    ///
+    /// The returned range may include more ledgers than requested, and never
+    /// starts before ledger 2 since the genesis ledger can't be replayed.
+    pub fn from_checkpoint_aligned(from: u32, to: u32) -> BoundedRange {
+        let from = from - from % CHECKPOINT_FREQUENCY;
+        let to = to | (CHECKPOINT_FREQUENCY - 1);
+
+        BoundedRange(from.max(2), to)
+    }
+
+    /// Gets the checkpoint ledgers within `range`, in ascending order.
+    pub fn checkpoint_ledgers_in_range(range: &BoundedRange) -> Vec<u32> {
+        let first = range.0 | (CHECKPOINT_FREQUENCY - 1);
+        if first > range.1 {
+            return Vec::new();
+        }
+
+        (first..=range.1)
+            .step_by(CHECKPOINT_FREQUENCY as usize)
+            .collect()
+    }
+
+    /// Splits the range into at most `parts` contiguous, non-overlapping
+    /// sub-ranges of (almost) equal length, in ascending order.
+    pub fn split_into(&self, parts: usize) -> Vec<Range> {
+        match self {
+            Range::Bounded(BoundedRange(from, to)) => {
+                if from > to {
+                    return Vec::new();
+                }
+
+                let total = (*to - *from) as u64 + 1;
+                let parts = (parts.max(1) as u64).min(total);
+                let step = total / parts;
+                let remainder = total % parts;
+
+                let mut start = *from as u64;
+                (0..parts)
+                    .map(|i| {
+                        let len = step + u64::from(i < remainder);
+                        let range = BoundedRange(start as u32, (start + len - 1) as u32);
+                        start += len;
+
+                        Range::Bounded(range)
+                    })
+                    .collect()
+            }
+        }
+    }
 }
 
 /// Enum to represent different types of errors related to the CaptiveCore.
@@ -47,25 +160,189 @@ pub enum Error {
     /// An attempt was made to call the closing mechanism, but the core is running in single-thread mode.
     #[error("Called closing mechanism, but core is running in single-thread mode")]
     CloseOnSingleThread,
+
+    /// `config_override` is disabled but there is no `stellar-core.cfg` in the context directory.
+    #[error("No stellar-core.cfg found in context directory {0}")]
+    MissingConfig(String),
+
+    /// Error encountered while reading or writing a prepared ledgers file.
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Error encountered while encoding ledger metadata to XDR.
+    #[error("XDR error: {0}")]
+    Xdr(#[from] stellar_xdr::next::Error),
+
+    /// Error returned by a user-provided ledger processor.
+    #[error("Processor error: {0}")]
+    Processor(Box<dyn std::error::Error + Send + Sync>),
+
+    /// The range is empty (`from > to`) or starts before ledger 2,
+    /// since ledger 1 is the genesis ledger and can't be replayed.
+    #[error("Invalid range from {from} to {to}")]
+    InvalidRange { from: u32, to: u32 },
+
+    /// The stellar-core executable is older than `min_stellar_core_version`.
+    #[error("stellar-core version {found} is older than the required {required}")]
+    IncompatibleCoreVersion {
+        found: semver::Version,
+        required: semver::Version,
+    },
+
+    /// The prepared ledgers are not contiguous, `expected` is the first
+    /// missing sequence and `found` the next prepared one.
+    #[error("Sequence gap in prepared ledgers, expected {expected} but found {found}")]
+    SequenceGap { expected: u32, found: u32 },
+
+    /// The quorum set references a validator name that is not in
+    /// `IngestionConfig::validators`.
+    #[error("Quorum set validator {0} is not in the configured validators")]
+    UnknownQuorumValidator(String),
 }
 
 /// Represents a captive instance of the Stellar Core.
 pub struct CaptiveCore {
     /// The Stellar Core runner associated with the captive instance.
     pub stellar_core_runner: StellarCoreRunner,
+
+    checkpoint_every: Option<(u32, PathBuf)>,
+
+    keep_data_on_drop: bool,
+}
+
+impl Drop for CaptiveCore {
+    /// Removes the buckets and the database from the context directory,
+    /// unless `keep_data_on_drop` is set. The rest of the context directory,
+    /// including `stellar-core.cfg`, is left untouched.
+    ///
+    /// A running stellar-core process is stopped first, so it doesn't keep
+    /// writing to the data being removed.
+    fn drop(&mut self) {
+        self.stellar_core_runner.stop_process();
+
+        if !self.keep_data_on_drop {
+            let _ = self.stellar_core_runner.remove_temp_data();
+        }
+    }
 }
 
 impl CaptiveCore {
     /// Creates a new CaptiveCore instance
-    pub fn new(config: IngestionConfig) -> Self {
-        // generate configs in path
-        generate_predefined_cfg(&config.context_path.0, config.network);
+    ///
+    /// # Returns
+    ///
+    /// Returns the `CaptiveCore`, `Error::MissingConfig` if `config_override`
+    /// is disabled and the context directory has no `stellar-core.cfg`,
+    /// `Error::UnknownQuorumValidator` if `quorum_set` references a validator
+    /// missing from `validators`, or `Error::IncompatibleCoreVersion` if the
+    /// executable is older than `min_stellar_core_version`.
+    pub fn new(config: IngestionConfig) -> Result<Self, Error> {
+        if let Some(quorum_set) = config.quorum_set.as_ref() {
+            if let Some(name) = quorum_set.unknown_validator(&config.validators) {
+                return Err(Error::UnknownQuorumValidator(name.to_string()));
+            }
+        }
+
+        if config.config_override {
+            // generate configs in path
+            generate_predefined_cfg(
+                &config.context_path.0,
+                config.network,
+                &config.validators,
+                config.quorum_set.as_ref(),
+                config.http_port,
+                &config.history_archive_headers,
+                &CatchupLimits {
+                    catchup_complete: config.catchup_complete,
+                    catchup_recent: config.catchup_recent,
+                    max_slots_to_remember: config.max_slots_to_remember,
+                },
+            );
+        } else if !Path::new(&config.context_path.0)
+            .join("stellar-core.cfg")
+            .exists()
+        {
+            return Err(Error::MissingConfig(config.context_path.0));
+        }
+
+        let checkpoint_every = config.checkpoint_every.clone();
+        let keep_data_on_drop = config.keep_data_on_drop;
+        let min_version = config.min_stellar_core_version.clone();
+        let stellar_core_runner = StellarCoreRunner::new(config);
+
+        if let Some(required) = min_version {
+            let found = stellar_core_runner.detect_version()?;
+            if found < required {
+                return Err(Error::IncompatibleCoreVersion { found, required });
+            }
+        }
+
+        Ok(Self {
+            checkpoint_every,
+            keep_data_on_drop,
+            stellar_core_runner,
+        })
+    }
+
+    /// Creates a `CaptiveCore` with the given ledgers already prepared, e.g.
+    /// to test code using the prepared ledgers without running stellar-core.
+    ///
+    /// The runner is never started, and nothing is written to or removed
+    /// from its context directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `prepared` - The prepared ledgers, in the order they were prepared.
+    #[cfg(any(test, feature = "testing"))]
+    pub fn new_with_mock(prepared: Vec<LedgerCloseMeta>) -> Self {
+        let mut stellar_core_runner = StellarCoreRunner::new(IngestionConfig {
+            executable_path: String::new(),
+            context_path: crate::ContextPath::auto_unique(),
+            network: SupportedNetwork::Pubnet,
+            config_override: false,
+            keep_data_on_drop: true,
+            ..Default::default()
+        });
+
+        stellar_core_runner.set_prepared(
+            prepared
+                .into_iter()
+                .map(|ledger| MetaResult {
+                    ledger_close_meta: Some(LedgerCloseMetaWrapper {
+                        ledger_close_meta: ledger,
+                    }),
+                    err: None,
+                })
+                .collect(),
+        );
 
         Self {
-            stellar_core_runner: StellarCoreRunner::new(config),
+            checkpoint_every: None,
+            keep_data_on_drop: true,
+            stellar_core_runner,
         }
     }
 
+    /// Regenerates the predefined `stellar-core.cfg` for the given network in
+    /// the context directory, overwriting the existing configuration. The
+    /// validators and quorum set of the config the captive core was created
+    /// with are kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - The network to generate the configuration for.
+    pub fn update_config(&self, network: SupportedNetwork) {
+        generate_predefined_cfg(
+            self.stellar_core_runner.context_path(),
+            network,
+            self.stellar_core_runner.validators(),
+            self.stellar_core_runner.quorum_set(),
+            self.stellar_core_runner.http_port(),
+            self.stellar_core_runner.history_archive_headers(),
+            self.stellar_core_runner.catchup_limits(),
+        );
+    }
+
     fn offline_replay_single_thread(&mut self, from: u32, to: u32) -> Result<(), Error> {
         // TODO: get archiver last checkpoint ledger for error accuracy.
 
@@ -84,25 +361,40 @@ impl CaptiveCore {
 
     /// Prepares ledgers in single-thread mode based on the specified range.
     ///
+    /// The prepared ledgers are returned as a [`LedgerBatch`]. The batch shares
+    /// them with the captive core, without copying, so they also stay available
+    /// through the captive core (e.g. `get_ledger` or `stream_ledgers`) until
+    /// the next preparation.
+    ///
     /// # Arguments
     ///
     /// * `range` - The range of ledgers to prepare.
     ///
     /// # Returns
     ///
-    /// Returns `Ok(())` if preparation is successful, or an `Error` if an issue occurs.
-    pub fn prepare_ledgers_single_thread(&mut self, range: &Range) -> Result<(), Error> {
+    /// Returns the prepared ledgers if preparation is successful, or an `Error` if an issue occurs.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    pub fn prepare_ledgers_single_thread(&mut self, range: &Range) -> Result<LedgerBatch, Error> {
+        validate_range(range)?;
+
         match range {
             Range::Bounded(range) => {
                 self.offline_replay_single_thread(range.0, range.1)?;
             }
         };
 
-        Ok(())
+        Ok(LedgerBatch::new(
+            self.stellar_core_runner
+                .shared_prepared()
+                .unwrap_or_default(),
+        ))
     }
 
     /// Prepares ledgers in multi-thread mode based on the specified range.
     ///
+    /// If `checkpoint_every` is set in the config, the received ledgers within
+    /// `range` are also appended to the checkpoint file.
+    ///
     /// # Arguments
     ///
     /// * `range` - The range of ledgers to prepare.
@@ -111,17 +403,186 @@ impl CaptiveCore {
     ///
     /// Returns a channel receiver for receiving metadata results if preparation is successful,
     /// or an `Error` if an issue occurs.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
     pub fn prepare_ledgers_multi_thread(
         &mut self,
         range: &Range,
     ) -> Result<Receiver<Box<MetaResult>>, Error> {
-        let receiver = match range {
-            Range::Bounded(range) => self.offline_replay_multi_thread(range.0, range.1)?,
+        validate_range(range)?;
+
+        if let (Range::Bounded(range), Some(step_size)) =
+            (range, self.stellar_core_runner.stagger_step_size())
+        {
+            // mirrors the split in `StellarCoreRunner::catchup_multi_thread`.
+            if (range.1 - range.0) / step_size <= 1 {
+                logging::warning!(
+                    "Staggering is ineffective: stagger_step_size {} doesn't split the range {}..={} in at least two steps",
+                    step_size,
+                    range.0,
+                    range.1
+                );
+            }
+        }
+
+        let (from, to) = match range {
+            Range::Bounded(range) => (range.0, range.1),
         };
+        let receiver = self.offline_replay_multi_thread(from, to)?;
+
+        match &self.checkpoint_every {
+            Some((every, path)) => Ok(checkpoint_ledgers(receiver, *every, path.clone(), from, to)),
+            None => Ok(receiver),
+        }
+    }
+
+    /// Prepares ledgers in multi-thread mode and calls `processor` on each of them.
+    ///
+    /// Ledgers that the catchup prepares outside of `range` are skipped, and the
+    /// runner is closed once the catchup job finishes.
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - The range of ledgers to replay.
+    /// * `processor` - Function called with every ledger, in ascending order.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` once every ledger was processed, `Error::Processor`
+    /// holding the error of `processor` if it fails, or an `Error` if reading
+    /// a ledger fails. If closing the runner also fails, the processing error
+    /// is returned and the closing error is logged.
+    ///
+    /// The processor's errors must be `Send` and `Sync` so that `Error` can be
+    /// returned by [`AsyncCaptiveCore`](crate::AsyncCaptiveCore), and callers
+    /// can downcast them back to their own error types.
+    pub fn replay_with_processor<F>(&mut self, range: &Range, mut processor: F) -> Result<(), Error>
+    where
+        F: FnMut(LedgerCloseMeta) -> Result<(), Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let (from, to) = match range {
+            Range::Bounded(range) => (range.0, range.1),
+        };
+        let receiver = self.prepare_ledgers_multi_thread(range)?;
+        let result = process_ledgers(&receiver, from, to, &mut processor);
+
+        match (result, self.close_runner_process()) {
+            (Err(error), Err(close_error)) => {
+                logging::error!(
+                    "Failed to close the runner after processing: {}",
+                    close_error
+                );
+                Err(error)
+            }
+            (Err(error), Ok(())) => Err(error),
+            (Ok(()), close_result) => close_result,
+        }
+    }
+
+    /// Prepares ledgers by running one catchup subprocess per sub-range.
+    ///
+    /// The range is split into `parallelism` non-overlapping sub-ranges with
+    /// `Range::split_into`, each caught up by its own stellar-core process in a
+    /// context directory suffixed with the sub-range index. Results are merged
+    /// into a single channel in ascending ledger sequence order, and each
+    /// subprocess and its context directory are cleaned up when it finishes.
+    ///
+    /// The ledgers each subprocess prepares outside of its sub-range because of
+    /// checkpoint alignment are dropped, as are the end sentinels of the
+    /// sub-ranges, so that every ledger of `range` is received exactly once.
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - The range of ledgers to prepare.
+    /// * `parallelism` - The amount of catchup subprocesses to run.
+    ///
+    /// # Returns
+    ///
+    /// Returns a channel receiver for receiving metadata results if all subprocesses
+    /// start successfully, `Error::InvalidRange` if `range` is invalid, or an `Error`
+    /// if an issue occurs, in which case the started subprocesses are closed.
+    pub fn prepare_ledgers_parallel(
+        &mut self,
+        range: &Range,
+        parallelism: usize,
+    ) -> Result<Receiver<Box<MetaResult>>, Error> {
+        validate_range(range)?;
+
+        let base_context_path = self.stellar_core_runner.context_path().to_string();
+        let mut sub_runners = Vec::new();
+
+        for (idx, sub_range) in range.split_into(parallelism).iter().enumerate() {
+            let context_path = format!("{}_{}", base_context_path, idx);
+            let (from, to) = sub_range.bounded();
+
+            let started = self.start_sub_range(&base_context_path, &context_path, from, to);
+            match started {
+                Ok((runner, receiver)) => sub_runners.push((runner, receiver, (from, to))),
+                Err(error) => {
+                    // don't leave the already started sub-ranges behind.
+                    let _ = fs::remove_dir_all(&context_path);
+                    for (runner, _, _) in sub_runners {
+                        close_sub_range(runner);
+                    }
+
+                    return Err(error);
+                }
+            }
+        }
+
+        let (transmitter, receiver) = std::sync::mpsc::channel();
+        thread::spawn(move || merge_sub_ranges(sub_runners, transmitter));
 
         Ok(receiver)
     }
 
+    // Starts the catchup of a sub-range of `prepare_ledgers_parallel` in
+    // `context_path`, with the config of `base_context_path`.
+    fn start_sub_range(
+        &self,
+        base_context_path: &str,
+        context_path: &str,
+        from: u32,
+        to: u32,
+    ) -> Result<(StellarCoreRunner, Receiver<Box<MetaResult>>), Error> {
+        fs::create_dir_all(context_path)?;
+        fs::copy(
+            Path::new(base_context_path).join("stellar-core.cfg"),
+            Path::new(context_path).join("stellar-core.cfg"),
+        )?;
+
+        let mut runner = self
+            .stellar_core_runner
+            .with_context_path(context_path.to_string());
+        match runner.catchup_multi_thread(from, to) {
+            Ok(receiver) => Ok((runner, receiver)),
+            Err(error) => {
+                let _ = runner.close_runner();
+                Err(error.into())
+            }
+        }
+    }
+
+    /// Retrieves the metrics of the ledgers processed by this instance.
+    ///
+    /// # Returns
+    ///
+    /// Returns the shared `IngestionMetrics`, updated as ledgers are read from stellar-core.
+    pub fn metrics(&self) -> Arc<IngestionMetrics> {
+        self.stellar_core_runner.metrics()
+    }
+
+    /// Returns whether the multi-thread mode channel is bounded. See
+    /// [`StellarCoreRunner::is_bounded_channel`].
+    pub fn is_bounded_channel(&self) -> bool {
+        self.stellar_core_runner.is_bounded_channel()
+    }
+
+    /// Returns the capacity of the multi-thread mode channel, or `None` if it
+    /// is unbounded. See [`StellarCoreRunner::bounded_channel_capacity`].
+    pub fn bounded_channel_capacity(&self) -> Option<usize> {
+        self.stellar_core_runner.bounded_channel_capacity()
+    }
+
     /// Closes the runner process in multi-thread mode.
     ///
     /// # Returns
@@ -151,18 +612,15 @@ impl CaptiveCore {
     ///
     /// Returns the `LedgerCloseMeta` if found, or an `Error` if the ledger is not found.
     pub fn get_ledger(&self, sequence: u32) -> Result<LedgerCloseMeta, Error> {
-        let prepared = self.stellar_core_runner.read_prepared();
+        let prepared = self
+            .stellar_core_runner
+            .prepared()
+            .ok_or(Error::LedgerNotFound)?;
 
         for ledger in prepared {
-            if let Some(wrapper) = ledger.ledger_close_meta {
-                let meta = wrapper.ledger_close_meta;
-                let ledger_seq = match meta.clone() {
-                    LedgerCloseMeta::V1(v1) => v1.ledger_header.header.ledger_seq,
-                    LedgerCloseMeta::V0(v0) => v0.ledger_header.header.ledger_seq,
-                };
-
-                if ledger_seq == sequence {
-                    return Ok(meta);
+            if let Ok(meta) = ledger.ledger_close_meta_ref() {
+                if HeaderReader::sequence(meta) == sequence {
+                    return Ok(meta.clone());
                 }
             }
         }
@@ -170,28 +628,944 @@ impl CaptiveCore {
         Err(Error::LedgerNotFound)
     }
 
-    pub async fn async_prepare_ledgers(&mut self, range: &Range, to_current: bool) -> Result<tokio::sync::mpsc::UnboundedReceiver<Box<MetaResult>>, Error> {
+    /// Retrieves the prepared ledgers within `range`, excluding the
+    /// additional ledgers prepared because of checkpoint alignment.
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - The inclusive range of ledger sequences to retrieve.
+    ///
+    /// # Returns
+    ///
+    /// Returns the ledgers in ascending sequence order, or `Error::LedgerNotFound`
+    /// if none of the prepared ledgers is within the range.
+    pub fn get_ledgers_in_range(
+        &self,
+        range: &BoundedRange,
+    ) -> Result<Vec<LedgerCloseMeta>, Error> {
+        let mut ledgers: Vec<(u32, &LedgerCloseMetaWrapper)> = self
+            .stellar_core_runner
+            .prepared()
+            .into_iter()
+            .flatten()
+            .filter_map(|result| {
+                let sequence = LedgerCloseMetaReader::ledger_sequence(result).ok()?;
+                let wrapper = result.ledger_close_meta.as_ref()?;
+
+                (sequence >= range.0 && sequence <= range.1).then_some((sequence, wrapper))
+            })
+            .collect();
+
+        if ledgers.is_empty() {
+            return Err(Error::LedgerNotFound);
+        }
+
+        ledgers.sort_by_key(|(sequence, _)| *sequence);
+
+        Ok(ledgers
+            .into_iter()
+            .map(|(_, wrapper)| wrapper.ledger_close_meta.clone())
+            .collect())
+    }
+
+    /// Processes the prepared ledgers in parallel on the rayon thread pool.
+    ///
+    /// Results that failed to decode are skipped, like in `get_ledgers_in_range`.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The closure called on each prepared ledger.
+    ///
+    /// # Returns
+    ///
+    /// Returns the output of `f` for each ledger in ascending sequence order, or
+    /// `Error::LedgerNotFound` if ledgers were not prepared with
+    /// `prepare_ledgers_single_thread`.
+    #[cfg(feature = "rayon")]
+    pub fn parallel_process<F, T>(&self, f: F) -> Result<Vec<T>, Error>
+    where
+        F: Fn(LedgerCloseMeta) -> T + Sync,
+        T: Send,
+    {
+        use rayon::prelude::*;
+
+        let prepared = self
+            .stellar_core_runner
+            .prepared()
+            .ok_or(Error::LedgerNotFound)?;
+
+        let mut ledgers: Vec<(u32, &LedgerCloseMetaWrapper)> = prepared
+            .iter()
+            .filter_map(|result| {
+                let sequence = LedgerCloseMetaReader::ledger_sequence(result).ok()?;
+                Some((sequence, result.ledger_close_meta.as_ref()?))
+            })
+            .collect();
+        ledgers.sort_by_key(|(sequence, _)| *sequence);
+
+        // indexed parallel iterators collect in their original order.
+        Ok(ledgers
+            .into_par_iter()
+            .map(|(_, wrapper)| f(wrapper.ledger_close_meta.clone()))
+            .collect())
+    }
+
+    /// Gets the amount of prepared results, which may include ledgers
+    /// outside of the requested range at checkpoint boundaries.
+    ///
+    /// # Returns
+    ///
+    /// Returns the amount of prepared results, `0` if ledgers were not
+    /// prepared with `prepare_ledgers_single_thread`.
+    pub fn prepared_ledger_count(&self) -> usize {
+        self.stellar_core_runner
+            .prepared()
+            .map(|prepared| prepared.len())
+            .unwrap_or(0)
+    }
+
+    /// Gets the lowest sequence among the prepared ledgers.
+    ///
+    /// # Returns
+    ///
+    /// Returns the sequence, or `None` if no ledger was prepared.
+    pub fn first_prepared_sequence(&self) -> Option<u32> {
+        self.prepared_sequences().min()
+    }
+
+    /// Gets the highest sequence among the prepared ledgers, e.g. to
+    /// resume an incremental catchup from the following ledger.
+    ///
+    /// # Returns
+    ///
+    /// Returns the sequence, or `None` if no ledger was prepared.
+    pub fn last_prepared_sequence(&self) -> Option<u32> {
+        self.prepared_sequences().max()
+    }
+
+    /// Checks that the prepared ledgers form a contiguous range of sequences.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Error::SequenceGap` for the first gap found, or
+    /// `Error::LedgerNotFound` if no ledger was prepared.
+    pub fn verify_sequence_continuity(&self) -> Result<(), Error> {
+        let mut sequences: Vec<u32> = self.prepared_sequences().collect();
+        sequences.sort_unstable();
+        sequences.dedup();
+
+        if sequences.is_empty() {
+            return Err(Error::LedgerNotFound);
+        }
+
+        for pair in sequences.windows(2) {
+            if pair[1] != pair[0] + 1 {
+                return Err(Error::SequenceGap {
+                    expected: pair[0] + 1,
+                    found: pair[1],
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks whether the prepared ledgers have gaps in their sequences,
+    /// see `verify_sequence_continuity`.
+    pub fn has_sequence_gaps(&self) -> bool {
+        matches!(
+            self.verify_sequence_continuity(),
+            Err(Error::SequenceGap { .. })
+        )
+    }
+
+    fn prepared_sequences(&self) -> impl Iterator<Item = u32> + '_ {
+        self.stellar_core_runner
+            .prepared()
+            .into_iter()
+            .flatten()
+            .filter_map(|result| LedgerCloseMetaReader::ledger_sequence(result).ok())
+    }
+
+    /// Lazily streams the prepared ledgers in ascending sequence order.
+    ///
+    /// Prepared results are sorted once and every `LedgerCloseMeta` is only cloned
+    /// when it is yielded, so the ledgers are never all held in a new `Vec` at once.
+    /// Errors in individual results are yielded as `Err(Error::Core(..))` items
+    /// without stopping the iteration.
+    ///
+    /// # Returns
+    ///
+    /// Returns an iterator over the prepared ledgers. If ledgers were not prepared
+    /// with `prepare_ledgers_single_thread` the iterator only yields `Error::LedgerNotFound`.
+    pub fn stream_ledgers(&self) -> impl Iterator<Item = Result<LedgerCloseMeta, Error>> + '_ {
+        let prepared = self.stellar_core_runner.prepared();
+        let not_prepared = prepared.is_none().then_some(Err(Error::LedgerNotFound));
+
+        let mut sorted: Vec<&MetaResult> = prepared.into_iter().flatten().collect();
+        // errors have no sequence and are kept at the end.
+        sorted.sort_by_key(|result| {
+            LedgerCloseMetaReader::ledger_sequence(result).unwrap_or(u32::MAX)
+        });
+
+        not_prepared
+            .into_iter()
+            .chain(sorted.into_iter().map(meta_result_to_ledger))
+    }
+
+    /// Async counterpart of `stream_ledgers` which streams the ledgers of the
+    /// specified range as they are emitted by the catchup.
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - The range of ledgers to prepare.
+    /// * `to_current` - Whether the catchup should target the current ledger.
+    ///
+    /// # Returns
+    ///
+    /// Returns a receiver of ledgers in the order they are emitted by stellar-core
+    /// (ascending for catchups), with errors as `Err(Error::Core(..))` items.
+    pub async fn async_stream_ledgers(
+        &mut self,
+        range: &Range,
+        to_current: bool,
+    ) -> Result<tokio::sync::mpsc::UnboundedReceiver<Result<LedgerCloseMeta, Error>>, Error> {
+        let mut meta_receiver = self.async_prepare_ledgers(range, to_current, None).await?;
+        let (transmitter, receiver) = tokio::sync::mpsc::unbounded_channel();
+
+        tokio::spawn(async move {
+            while let Some(result) = meta_receiver.recv().await {
+                if transmitter.send(meta_result_to_ledger(&result)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(receiver)
+    }
+
+    /// Exports the prepared ledgers to a file so that later runs can skip the catchup.
+    ///
+    /// Every `LedgerCloseMeta` is written with the same length-prefixed XDR
+    /// framing stellar-core uses for its metadata output stream. Results holding
+    /// an error are not exported.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the file to write, overwritten if it already exists.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if the export is successful, `Error::LedgerNotFound` if no
+    /// ledgers were prepared, or an `Error` if writing fails.
+    pub fn export_prepared_to_xdr_file(&self, path: &Path) -> Result<(), Error> {
+        let prepared = self
+            .stellar_core_runner
+            .prepared()
+            .ok_or(Error::LedgerNotFound)?;
+        let mut file = BufWriter::new(File::create(path)?);
+
+        for wrapper in prepared
+            .iter()
+            .filter_map(|result| result.ledger_close_meta.as_ref())
+        {
+            write_framed(&mut file, &wrapper.ledger_close_meta)?;
+        }
+
+        file.flush()?;
+
+        Ok(())
+    }
+
+    /// Resumes a checkpointed catchup up to `target_end`.
+    ///
+    /// The ledgers already in the checkpoint file are sent first, followed by
+    /// the ledgers of a new multi-thread catchup starting right after the last
+    /// checkpointed ledger. The ledgers the catchup prepares again because of
+    /// checkpoint alignment are not sent twice. A partially written frame at the end of the file,
+    /// e.g. from a crash while checkpointing, is dropped from the file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the checkpoint file, see `IngestionConfig::checkpoint_every`.
+    /// * `target_end` - Last ledger of the resumed range.
+    ///
+    /// # Returns
+    ///
+    /// Returns a channel receiver for receiving metadata results, `Error::LedgerNotFound`
+    /// if the checkpoint holds no ledgers, or an `Error` if an issue occurs.
+    pub fn resume_from_checkpoint(
+        &mut self,
+        path: &Path,
+        target_end: u32,
+    ) -> Result<Receiver<Box<MetaResult>>, Error> {
+        let reader = BufferedLedgerMetaReader::new(
+            BufferedLedgerMetaReaderMode::SingleThread,
+            Box::new(File::open(path)?),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .map_err(RunnerError::MetaReader)?;
+
+        let mut checkpointed = Vec::new();
+        let mut truncated = false;
+        for result in reader {
+            if result.ledger_close_meta.is_none() {
+                truncated = true;
+                break;
+            }
+
+            checkpointed.push(result);
+        }
+
+        let last_seq = match checkpointed.last() {
+            Some(result) => {
+                LedgerCloseMetaReader::ledger_sequence(result).map_err(|_| Error::LedgerNotFound)?
+            }
+            None => return Err(Error::LedgerNotFound),
+        };
+
+        if truncated {
+            let mut file = BufWriter::new(File::create(path)?);
+            for wrapper in checkpointed
+                .iter()
+                .filter_map(|result| result.ledger_close_meta.as_ref())
+            {
+                write_framed(&mut file, &wrapper.ledger_close_meta)?;
+            }
+
+            file.flush()?;
+        }
+
+        let catchup = if last_seq < target_end {
+            let range = Range::Bounded(BoundedRange(last_seq + 1, target_end));
+            Some(self.prepare_ledgers_multi_thread(&range)?)
+        } else {
+            None
+        };
+
+        let (transmitter, receiver) = std::sync::mpsc::channel();
+        thread::spawn(move || {
+            for result in checkpointed {
+                if transmitter.send(Box::new(result)).is_err() {
+                    return;
+                }
+            }
+
+            let resumed = catchup
+                .iter()
+                .flat_map(|catchup| catchup.iter())
+                .filter(|result| !is_checkpointed(result, last_seq));
+
+            for result in resumed {
+                if transmitter.send(result).is_err() {
+                    return;
+                }
+            }
+        });
+
+        Ok(receiver)
+    }
+
+    /// Imports ledgers previously exported with `export_prepared_to_xdr_file`
+    /// as the prepared ledgers, without running stellar-core.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the file to read.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if the import is successful, or an `Error` if the file can't be read.
+    /// Frames that fail to decode are kept as results holding the error.
+    pub fn import_prepared_from_xdr_file(&mut self, path: &Path) -> Result<(), Error> {
+        let reader = BufferedLedgerMetaReader::new(
+            BufferedLedgerMetaReaderMode::SingleThread,
+            Box::new(File::open(path)?),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .map_err(RunnerError::MetaReader)?;
+
+        self.stellar_core_runner.set_prepared(reader.collect());
+
+        Ok(())
+    }
+
+    /// Prepares a range of ledgers asynchronously.
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - The range of ledgers to prepare.
+    /// * `to_current` - Whether the catchup should target the current ledger.
+    /// * `shutdown` - Optional cancellation signal. Setting it to `true` kills
+    ///   the stellar-core process and closes the returned receiver.
+    ///
+    /// # Returns
+    ///
+    /// Returns a receiver for the metadata results, or an `Error` if the runner
+    /// can't be started.
+    pub async fn async_prepare_ledgers(
+        &mut self,
+        range: &Range,
+        to_current: bool,
+        shutdown: Option<watch::Receiver<bool>>,
+    ) -> Result<AsyncMetaReceiver, Error> {
         match range {
             Range::Bounded(range) => {
-                self.stellar_core_runner.async_catchup_multi_thread(range.0, range.1, to_current).await.map_err(|runner| Error::Core(runner))
+                self.stellar_core_runner.async_catchup_multi_thread(range.0, range.1, to_current, shutdown).await.map_err(|runner| Error::Core(runner))
             }
         }
     }
 
+    /// Prepares a range of ledgers asynchronously as a [`LedgerStream`].
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - The range of ledgers to prepare.
+    /// * `to_current` - Whether the catchup should target the current ledger.
+    ///
+    /// # Returns
+    ///
+    /// Returns a stream of ledgers in the order they are emitted by stellar-core,
+    /// or an `Error` if the runner can't be started.
+    pub async fn into_ledger_stream_async(
+        &mut self,
+        range: &Range,
+        to_current: bool,
+    ) -> Result<LedgerStream, Error> {
+        let receiver = self.async_prepare_ledgers(range, to_current, None).await?;
+
+        Ok(receiver.into())
+    }
+
     /// Starts the runner in online mode without specifying a range.
     ///
     /// # Returns
     ///
     /// Returns a channel receiver for receiving metadata results if the runner starts successfully,
     /// or an `Error` if an issue occurs.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
     pub fn start_online_no_range(&mut self) -> Result<Receiver<Box<MetaResult>>, Error> {
         Ok(self.stellar_core_runner.run()?)
     }
 
+    /// Starts the runner in online mode and only streams the contract events
+    /// of each ledger, e.g. for event monitoring.
+    ///
+    /// The events are read on a separate thread, see [`filter_map_ledgers`].
+    ///
+    /// # Arguments
+    ///
+    /// * `skip_empty` - Whether to skip ledgers without events. When `false` an
+    ///   empty `Vec` is sent for them, so that ledger boundaries can be detected.
+    ///
+    /// # Returns
+    ///
+    /// Returns a channel receiver for receiving the events of each ledger, in
+    /// the order they were emitted, or an `Error` if the runner can't be started.
+    pub fn stream_online_events_only(
+        &mut self,
+        skip_empty: bool,
+    ) -> Result<Receiver<Vec<ContractEvent>>, Error> {
+        let receiver = self.start_online_no_range()?;
+
+        Ok(filter_map_ledgers(receiver, move |ledger| {
+            let events: Vec<ContractEvent> =
+                SorobanEventReader(&ledger).events_iter().cloned().collect();
+
+            if skip_empty && events.is_empty() {
+                None
+            } else {
+                Some(events)
+            }
+        }))
+    }
 
-    pub async fn async_start_online_no_range(&mut self) -> Result<tokio::sync::mpsc::UnboundedReceiver<Box<MetaResult>>, Error> {
+    /// Starts the runner in online mode after catching up the last
+    /// `lookback_ledgers` ledgers, e.g. to index recent history before
+    /// streaming new ledgers. See `StellarCoreRunner::run_with_initial_catchup`.
+    ///
+    /// # Arguments
+    ///
+    /// * `lookback_ledgers` - The amount of ledgers up to the current one to catch up.
+    ///
+    /// # Returns
+    ///
+    /// Returns a channel receiver for receiving both the caught up and the live
+    /// metadata results, or an `Error` if the catchup fails to start.
+    pub fn start_online_with_initial_catchup(
+        &mut self,
+        lookback_ledgers: u32,
+    ) -> Result<Receiver<Box<MetaResult>>, Error> {
+        Ok(self
+            .stellar_core_runner
+            .run_with_initial_catchup(lookback_ledgers)?)
+    }
+
+    /// Starts the runner in online mode, sending every ledger to `subscriber_count`
+    /// receivers.
+    ///
+    /// Each subscriber has its own bounded channel, so a slow subscriber blocks
+    /// the others until it catches up. See `StellarCoreRunner::run_broadcast`.
+    ///
+    /// # Returns
+    ///
+    /// Returns one channel receiver per subscriber if the runner starts successfully,
+    /// or an `Error` if an issue occurs.
+    pub fn start_online_broadcast(
+        &mut self,
+        subscriber_count: usize,
+    ) -> Result<Vec<Receiver<Box<MetaResult>>>, Error> {
+        Ok(self.stellar_core_runner.run_broadcast(subscriber_count)?)
+    }
+
+    pub async fn async_start_online_no_range(&mut self) -> Result<AsyncMetaReceiver, Error> {
         Ok(self.stellar_core_runner.run_async().await?)
     }
 
     // TODO: method to start from ledger.
 }
+
+// Makes sure `range` can be passed to a stellar-core catchup.
+fn validate_range(range: &Range) -> Result<(), Error> {
+    match range {
+        Range::Bounded(range) if !range.is_valid() => Err(Error::InvalidRange {
+            from: range.0,
+            to: range.1,
+        }),
+        Range::Bounded(_) => Ok(()),
+    }
+}
+
+// Calls `processor` on the ledgers in `from..=to` received until the catchup
+// job finishes, which closes the pipe.
+fn process_ledgers<F>(
+    receiver: &Receiver<Box<MetaResult>>,
+    from: u32,
+    to: u32,
+    processor: &mut F,
+) -> Result<(), Error>
+where
+    F: FnMut(LedgerCloseMeta) -> Result<(), Box<dyn std::error::Error + Send + Sync>>,
+{
+    for result in receiver.iter() {
+        let ledger_seq = LedgerCloseMetaReader::ledger_sequence(&result).ok();
+
+        if result.is_catchup_end_sentinel() {
+            break;
+        }
+
+        match (result.ledger_close_meta, result.err) {
+            (Some(wrapper), _) => {
+                if ledger_seq.is_some_and(|seq| seq >= from && seq <= to) {
+                    processor(wrapper.ledger_close_meta).map_err(Error::Processor)?;
+                }
+            }
+
+            (None, error) => {
+                return Err(Error::Core(RunnerError::MetaReader(
+                    error.unwrap_or(BufReaderError::ReadXdrNext),
+                )))
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Writes `meta` with the length-prefixed XDR framing of stellar-core's
+// metadata output stream.
+fn write_framed(writer: &mut impl Write, meta: &LedgerCloseMeta) -> Result<(), Error> {
+    let xdr = meta.to_xdr(Limits::none())?;
+
+    // the high bit marks the last (and only) fragment of the record.
+    let header = xdr.len() as u32 | 0x8000_0000;
+    writer.write_all(&header.to_be_bytes())?;
+    writer.write_all(&xdr)?;
+
+    Ok(())
+}
+
+// Whether a result of the catchup resuming after `last_seq` is already in the
+// checkpoint file. The catchup starts from the checkpoint before
+// `last_seq + 1`, so it prepares checkpointed ledgers again.
+fn is_checkpointed(result: &MetaResult, last_seq: u32) -> bool {
+    result
+        .ledger_sequence()
+        .is_some_and(|sequence| sequence <= last_seq)
+}
+
+fn append_checkpoint(path: &Path, frames: &[u8]) -> Result<(), Error> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+
+    // a single write keeps interrupted checkpoints to at most one partial frame.
+    file.write_all(frames)?;
+    file.flush()?;
+
+    Ok(())
+}
+
+// Forwards the results of `receiver`, appending the framed ledgers within
+// `from..=to` to the checkpoint file at `path` every `every` ledgers and once
+// the catchup ends. Ledgers outside of the range, prepared because of
+// checkpoint alignment, are only forwarded so that resuming doesn't find
+// them twice in the file.
+fn checkpoint_ledgers(
+    receiver: Receiver<Box<MetaResult>>,
+    every: u32,
+    path: PathBuf,
+    from: u32,
+    to: u32,
+) -> Receiver<Box<MetaResult>> {
+    // rendezvous channel so that the upstream channel keeps its back-pressure.
+    let (transmitter, checkpointed) = std::sync::mpsc::sync_channel(0);
+    let every = every.max(1);
+
+    thread::spawn(move || {
+        let mut frames = Vec::new();
+        let mut pending = 0;
+
+        for result in receiver.iter() {
+            let in_range = result
+                .ledger_sequence()
+                .is_some_and(|sequence| (from..=to).contains(&sequence));
+            if let Some(wrapper) = result.ledger_close_meta.as_ref().filter(|_| in_range) {
+                match write_framed(&mut frames, &wrapper.ledger_close_meta) {
+                    Ok(()) => pending += 1,
+                    Err(error) => logging::error!("Failed to encode checkpoint ledger: {}", error),
+                }
+            }
+
+            if pending >= every {
+                if let Err(error) = append_checkpoint(&path, &frames) {
+                    logging::error!("Failed to write checkpoint: {}", error);
+                }
+
+                frames.clear();
+                pending = 0;
+            }
+
+            if transmitter.send(result).is_err() {
+                break;
+            }
+        }
+
+        if pending > 0 {
+            if let Err(error) = append_checkpoint(&path, &frames) {
+                logging::error!("Failed to write checkpoint: {}", error);
+            }
+        }
+    });
+
+    checkpointed
+}
+
+fn meta_result_to_ledger(result: &MetaResult) -> Result<LedgerCloseMeta, Error> {
+    match &result.ledger_close_meta {
+        Some(wrapper) => Ok(wrapper.ledger_close_meta.clone()),
+        None => Err(Error::Core(RunnerError::MetaReader(
+            result.err.clone().unwrap_or(BufReaderError::ReadXdrNext),
+        ))),
+    }
+}
+
+// runner of a sub-range of `prepare_ledgers_parallel`, its stream and its bounds.
+type SubRangeRunner = (StellarCoreRunner, Receiver<Box<MetaResult>>, (u32, u32));
+
+// Merges the ascending streams of the sub-range runners into a single
+// ascending stream, cleaning up every runner once its stream ends.
+//
+// Catchups start from the checkpoint before their range, so each stream only
+// contributes the ledgers within its own sub-range and the merged stream has
+// no duplicates.
+fn merge_sub_ranges(sub_runners: Vec<SubRangeRunner>, transmitter: Sender<Box<MetaResult>>) {
+    let mut runners = Vec::with_capacity(sub_runners.len());
+    let mut receivers = Vec::with_capacity(sub_runners.len());
+    let mut bounds = Vec::with_capacity(sub_runners.len());
+    for (runner, receiver, bound) in sub_runners {
+        runners.push(Some(runner));
+        receivers.push(receiver);
+        bounds.push(bound);
+    }
+
+    // every sub-range has at most one pending ledger, ordered by sequence in the heap.
+    let mut pending: Vec<Option<Box<MetaResult>>> = receivers.iter().map(|_| None).collect();
+    let mut heads = BinaryHeap::new();
+
+    // pulls the next ledger of a sub-range. Errors carry no sequence so they
+    // are forwarded right away, except for the end sentinel of the sub-range
+    // which would end the merged stream early.
+    let mut pull = |idx: usize,
+                    heads: &mut BinaryHeap<(Reverse<u32>, usize)>,
+                    pending: &mut Vec<Option<Box<MetaResult>>>|
+     -> bool {
+        let (from, to) = bounds[idx];
+
+        while let Ok(result) = receivers[idx].recv() {
+            match result.ledger_sequence() {
+                Some(sequence) if sequence < from || sequence > to => continue,
+                Some(sequence) => {
+                    heads.push((Reverse(sequence), idx));
+                    pending[idx] = Some(result);
+                    return true;
+                }
+                None if result.is_catchup_end_sentinel() => continue,
+                None => {
+                    if transmitter.send(result).is_err() {
+                        return false;
+                    }
+                }
+            }
+        }
+
+        if let Some(runner) = runners[idx].take() {
+            close_sub_range(runner);
+        }
+
+        true
+    };
+
+    'merge: {
+        for idx in 0..receivers.len() {
+            if !pull(idx, &mut heads, &mut pending) {
+                break 'merge;
+            }
+        }
+
+        while let Some((_, idx)) = heads.pop() {
+            // a head in the heap always has its pending ledger.
+            let result = pending[idx].take().unwrap();
+
+            if transmitter.send(result).is_err() || !pull(idx, &mut heads, &mut pending) {
+                break 'merge;
+            }
+        }
+    }
+
+    // the receiver was dropped, so the sub-ranges still running are stopped.
+    for runner in runners.into_iter().flatten() {
+        close_sub_range(runner);
+    }
+}
+
+// Closes the runner of a sub-range and removes its context directory.
+fn close_sub_range(mut runner: StellarCoreRunner) {
+    let _ = runner.close_runner();
+    let _ = fs::remove_dir_all(runner.context_path());
+}
+
+#[cfg(test)]
+mod tests {
+    use stellar_xdr::next::LedgerCloseMetaV0;
+
+    use super::*;
+    use crate::testing::encode_frames;
+
+    fn ledger(seq: u32) -> LedgerCloseMeta {
+        let mut v0 = LedgerCloseMetaV0::default();
+        v0.ledger_header.header.ledger_seq = seq;
+
+        LedgerCloseMeta::V0(v0)
+    }
+
+    fn meta_result(seq: u32) -> Box<MetaResult> {
+        Box::new(MetaResult {
+            ledger_close_meta: Some(LedgerCloseMetaWrapper {
+                ledger_close_meta: ledger(seq),
+            }),
+            err: None,
+        })
+    }
+
+    fn sequences(receiver: Receiver<Box<MetaResult>>) -> Vec<u32> {
+        receiver
+            .iter()
+            .map(|result| LedgerCloseMetaReader::ledger_sequence(&result).unwrap())
+            .collect()
+    }
+
+    fn checkpoint_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("rs_ingest_{}_{}.xdr", name, std::process::id()))
+    }
+
+    #[test]
+    fn checkpoints_only_ledgers_in_range() {
+        let path = checkpoint_path("in_range");
+        let _ = fs::remove_file(&path);
+
+        let (transmitter, receiver) = std::sync::mpsc::channel();
+        for seq in 8..=14 {
+            transmitter.send(meta_result(seq)).unwrap();
+        }
+        drop(transmitter);
+
+        let forwarded = checkpoint_ledgers(receiver, 2, path.clone(), 10, 12);
+        assert_eq!(sequences(forwarded), (8..=14).collect::<Vec<_>>());
+
+        let expected = encode_frames(&[ledger(10), ledger(11), ledger(12)]).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), expected);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn resume_cuts_partial_checkpoint_frame() {
+        let path = checkpoint_path("resume");
+        let frames = encode_frames(&[ledger(10), ledger(11), ledger(12)]).unwrap();
+        let tail = encode_frames(&[ledger(13)]).unwrap();
+
+        // the record mark and the first bytes of the next ledger.
+        let mut contents = frames.clone();
+        contents.extend_from_slice(&tail[..6]);
+        fs::write(&path, contents).unwrap();
+
+        let mut captive_core = CaptiveCore::new_with_mock(Vec::new());
+        let receiver = captive_core.resume_from_checkpoint(&path, 12).unwrap();
+
+        assert_eq!(sequences(receiver), vec![10, 11, 12]);
+        assert_eq!(fs::read(&path).unwrap(), frames);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn resumed_catchup_skips_checkpointed_ledgers() {
+        assert!(is_checkpointed(&meta_result(11), 12));
+        assert!(is_checkpointed(&meta_result(12), 12));
+        assert!(!is_checkpointed(&meta_result(13), 12));
+
+        // errors carry no sequence, so they are always forwarded.
+        let error = MetaResult {
+            ledger_close_meta: None,
+            err: Some(BufReaderError::ReadXdrNext),
+        };
+        assert!(!is_checkpointed(&error, 12));
+    }
+
+    fn split(from: u32, to: u32, parts: usize) -> Vec<(u32, u32)> {
+        Range::Bounded(BoundedRange(from, to))
+            .split_into(parts)
+            .into_iter()
+            .map(|Range::Bounded(range)| (range.0, range.1))
+            .collect()
+    }
+
+    #[test]
+    fn split_into_covers_range_without_overlaps() {
+        assert_eq!(split(10, 20, 3), vec![(10, 13), (14, 17), (18, 20)]);
+        assert_eq!(split(10, 20, 1), vec![(10, 20)]);
+        assert_eq!(split(10, 20, 0), vec![(10, 20)]);
+    }
+
+    #[test]
+    fn split_into_caps_parts_at_range_length() {
+        assert_eq!(split(10, 12, 5), vec![(10, 10), (11, 11), (12, 12)]);
+        assert!(split(12, 10, 3).is_empty());
+    }
+
+    // a sub-range runner with an existing context directory, streaming
+    // `sequences` followed by the end sentinel of its catchup.
+    fn sub_range(sequences: std::ops::RangeInclusive<u32>, bounds: (u32, u32)) -> SubRangeRunner {
+        let runner = StellarCoreRunner::new(IngestionConfig {
+            context_path: crate::ContextPath::auto_unique(),
+            ..Default::default()
+        });
+        fs::create_dir_all(runner.context_path()).unwrap();
+
+        let (transmitter, receiver) = std::sync::mpsc::channel();
+        for seq in sequences {
+            transmitter.send(meta_result(seq)).unwrap();
+        }
+        transmitter
+            .send(Box::new(MetaResult {
+                ledger_close_meta: None,
+                err: None,
+            }))
+            .unwrap();
+
+        (runner, receiver, bounds)
+    }
+
+    #[test]
+    fn merge_sub_ranges_is_ascending_without_duplicates() {
+        // catchups start from the checkpoint before their sub-range.
+        let sub_runners = vec![
+            sub_range(8..=13, (10, 13)),
+            sub_range(12..=18, (14, 17)),
+            sub_range(16..=20, (18, 20)),
+        ];
+        let context_paths: Vec<String> = sub_runners
+            .iter()
+            .map(|(runner, _, _)| runner.context_path().to_string())
+            .collect();
+
+        let (transmitter, receiver) = std::sync::mpsc::channel();
+        merge_sub_ranges(sub_runners, transmitter);
+
+        assert_eq!(sequences(receiver), (10..=20).collect::<Vec<_>>());
+        for context_path in context_paths {
+            assert!(!Path::new(&context_path).exists());
+        }
+    }
+
+    #[test]
+    fn merge_sub_ranges_cleans_up_when_receiver_is_dropped() {
+        let sub_runners = vec![sub_range(10..=13, (10, 13)), sub_range(14..=17, (14, 17))];
+        let context_paths: Vec<String> = sub_runners
+            .iter()
+            .map(|(runner, _, _)| runner.context_path().to_string())
+            .collect();
+
+        let (transmitter, receiver) = std::sync::mpsc::channel();
+        drop(receiver);
+        merge_sub_ranges(sub_runners, transmitter);
+
+        for context_path in context_paths {
+            assert!(!Path::new(&context_path).exists());
+        }
+    }
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("ledger {0} rejected")]
+    struct Rejected(u32);
+
+    #[test]
+    fn processor_errors_can_be_downcast() {
+        let (transmitter, receiver) = std::sync::mpsc::channel();
+        for seq in 10..=12 {
+            transmitter.send(meta_result(seq)).unwrap();
+        }
+        drop(transmitter);
+
+        let mut processed = Vec::new();
+        let result = process_ledgers(&receiver, 10, 12, &mut |ledger: LedgerCloseMeta| {
+            let seq = HeaderReader::sequence(&ledger);
+            if seq == 11 {
+                return Err(Rejected(seq).into());
+            }
+
+            processed.push(seq);
+            Ok(())
+        });
+
+        match result {
+            Err(Error::Processor(error)) => {
+                assert_eq!(
+                    error.downcast_ref::<Rejected>().map(|error| error.0),
+                    Some(11)
+                )
+            }
+            other => panic!("expected a processor error, got {:?}", other),
+        }
+        assert_eq!(processed, vec![10]);
+    }
+}
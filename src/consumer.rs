@@ -0,0 +1,147 @@
+use std::{
+    sync::mpsc::{channel, Receiver},
+    thread,
+};
+
+use stellar_xdr::next::LedgerCloseMeta;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+use crate::{logging, AsyncMetaReceiver, BufReaderError, MetaResult};
+
+/// Represents the errors that can stop [`process_each`] and [`async_process_each`].
+#[derive(thiserror::Error, Debug)]
+pub enum ProcessError {
+    /// The processing closure failed.
+    #[error("Ledger processing failed: {0}")]
+    Processor(Box<dyn std::error::Error>),
+
+    /// Error encountered while reading ledger metadata.
+    #[error("Error in reading ledger metadata: {0}")]
+    MetaReader(#[from] BufReaderError),
+}
+
+/// Calls `f` on every ledger received from a runner's receiver.
+///
+/// Processing stops once the receiver is closed or the catchup job finishes,
+/// so callers don't need to handle the end of stream results sent when the
+/// stellar-core pipe closes, see [`MetaResult::is_catchup_end_sentinel`].
+///
+/// # Arguments
+///
+/// * `rx` - The receiver returned by the runner APIs.
+/// * `f` - The closure called on each ledger, in the order they are received.
+///
+/// # Returns
+///
+/// Returns `Ok(())` once all ledgers were processed, or a `ProcessError` if
+/// reading a ledger or `f` fails.
+pub fn process_each<F>(rx: Receiver<Box<MetaResult>>, mut f: F) -> Result<(), ProcessError>
+where
+    F: FnMut(LedgerCloseMeta) -> Result<(), Box<dyn std::error::Error>>,
+{
+    for result in rx.iter() {
+        match next_ledger(*result)? {
+            Some(ledger) => f(ledger).map_err(ProcessError::Processor)?,
+            None => break,
+        }
+    }
+
+    Ok(())
+}
+
+/// Async version of [`process_each`].
+pub async fn async_process_each<F>(mut rx: AsyncMetaReceiver, mut f: F) -> Result<(), ProcessError>
+where
+    F: FnMut(LedgerCloseMeta) -> Result<(), Box<dyn std::error::Error>>,
+{
+    while let Some(result) = rx.recv().await {
+        match next_ledger(*result)? {
+            Some(ledger) => f(ledger).map_err(ProcessError::Processor)?,
+            None => break,
+        }
+    }
+
+    Ok(())
+}
+
+/// Maps the ledgers received from a runner's receiver into a new channel,
+/// dropping the ledgers for which `f` returns `None`.
+///
+/// The mapping runs on a separate thread. The returned channel is closed once
+/// the receiver is closed or the catchup job finishes, and ledgers that fail
+/// to be read are logged and skipped.
+///
+/// # Arguments
+///
+/// * `rx` - The receiver returned by the runner APIs.
+/// * `f` - The closure mapping each ledger, in the order they are received.
+///
+/// # Returns
+///
+/// Returns the receiver of the mapped values.
+pub fn filter_map_ledgers<T, F>(rx: Receiver<Box<MetaResult>>, mut f: F) -> Receiver<T>
+where
+    T: Send + 'static,
+    F: FnMut(LedgerCloseMeta) -> Option<T> + Send + 'static,
+{
+    let (transmitter, receiver) = channel();
+
+    thread::spawn(move || {
+        for result in rx.iter() {
+            match next_ledger(*result) {
+                Ok(Some(ledger)) => {
+                    if let Some(value) = f(ledger) {
+                        if transmitter.send(value).is_err() {
+                            break;
+                        }
+                    }
+                }
+                Ok(None) => break,
+                Err(error) => logging::error!("Skipping ledger that failed to be read: {}", error),
+            }
+        }
+    });
+
+    receiver
+}
+
+/// Async version of [`filter_map_ledgers`].
+///
+/// The mapping runs on a task spawned on the current tokio runtime.
+pub fn async_filter_map_ledgers<T, F>(mut rx: AsyncMetaReceiver, mut f: F) -> UnboundedReceiver<T>
+where
+    T: Send + 'static,
+    F: FnMut(LedgerCloseMeta) -> Option<T> + Send + 'static,
+{
+    let (transmitter, receiver) = unbounded_channel();
+
+    tokio::spawn(async move {
+        while let Some(result) = rx.recv().await {
+            match next_ledger(*result) {
+                Ok(Some(ledger)) => {
+                    if let Some(value) = f(ledger) {
+                        if transmitter.send(value).is_err() {
+                            break;
+                        }
+                    }
+                }
+                Ok(None) => break,
+                Err(error) => logging::error!("Skipping ledger that failed to be read: {}", error),
+            }
+        }
+    });
+
+    receiver
+}
+
+// Returns `None` once the pipe is closed, which happens when the catchup job finishes.
+fn next_ledger(result: MetaResult) -> Result<Option<LedgerCloseMeta>, BufReaderError> {
+    if result.is_catchup_end_sentinel() {
+        return Ok(None);
+    }
+
+    match (result.ledger_close_meta, result.err) {
+        (Some(wrapper), _) => Ok(Some(wrapper.ledger_close_meta)),
+        (None, error) => Err(error.unwrap_or(BufReaderError::ReadXdrNext)),
+    }
+}
@@ -1,18 +1,35 @@
-use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+use tokio::sync::{
+    mpsc::{UnboundedReceiver, UnboundedSender},
+    oneshot, watch,
+};
 
 use crate::{
-    BufReaderError, BufferedLedgerMetaReader, BufferedLedgerMetaReaderMode, IngestionConfig,
-    MetaResult, MultiThreadBufferedLedgerMetaReader, SingleThreadBufferedLedgerMetaReader,
+    logging, toml::CatchupLimits, BoundedRange, BufReaderError, BufferedLedgerMetaReader,
+    BufferedLedgerMetaReaderMode, CoreLogLevel, IngestionConfig, IngestionMetrics,
+    LedgerCloseMetaReader, MetaResult, MultiThreadBufferedLedgerMetaReader, QuorumSetConfig,
+    SingleThreadBufferedLedgerMetaReader, ValidatorEntry, CHECKPOINT_FREQUENCY,
+    LEDGER_READ_AHEAD_BUFFER_SIZE,
 };
 use std::{
+    fs,
     io::{self, BufReader},
-    process::{Child, ChildStdout, Command},
-    sync::mpsc::Receiver,
+    path::Path,
+    process::{Child, ChildStdout, Command, ExitStatus},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{Receiver, RecvTimeoutError},
+        Arc,
+    },
+    task::{Context, Poll},
     thread,
+    time::Duration,
 };
 
+// how often the watchdog checks on the stellar-core process.
+const WATCHDOG_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
 /// Represents the status of a core runner.
-#[derive(PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum RunnerStatus {
     /// The runner is actively processing tasks while disconnected from the network.
     RunningOffline,
@@ -22,6 +39,49 @@ pub enum RunnerStatus {
 
     /// The runner has been closed and is no longer processing tasks.
     Closed,
+
+    /// The runner's process exited and is being restarted by the watchdog.
+    Restarting,
+}
+
+/// Receiving end of the async runner APIs.
+///
+/// The channel is bounded when `IngestionConfig::bounded_buffer_size` is set,
+/// in which case reading from the stellar-core pipe pauses while the channel
+/// is full.
+pub enum AsyncMetaReceiver {
+    /// Receiver of an unbounded channel.
+    Unbounded(UnboundedReceiver<Box<MetaResult>>),
+
+    /// Receiver of a bounded channel.
+    Bounded(tokio::sync::mpsc::Receiver<Box<MetaResult>>),
+}
+
+impl AsyncMetaReceiver {
+    /// Receives the next metadata result.
+    ///
+    /// # Returns
+    ///
+    /// Returns `None` once the channel is closed and all results were received.
+    pub async fn recv(&mut self) -> Option<Box<MetaResult>> {
+        match self {
+            Self::Unbounded(receiver) => receiver.recv().await,
+            Self::Bounded(receiver) => receiver.recv().await,
+        }
+    }
+
+    /// Polls to receive the next metadata result.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Poll::Ready(None)` once the channel is closed and all results
+    /// were received.
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<Box<MetaResult>>> {
+        match self {
+            Self::Unbounded(receiver) => receiver.poll_recv(cx),
+            Self::Bounded(receiver) => receiver.poll_recv(cx),
+        }
+    }
 }
 
 /// Core runner object.
@@ -33,15 +93,40 @@ pub struct StellarCoreRunner {
 
     status: RunnerStatus,
 
+    // publishes every change of `status`, see `status_watch`.
+    status_tx: Arc<watch::Sender<RunnerStatus>>,
+
     ledger_buffer_reader: Option<BufferedLedgerMetaReader>,
 
-    prepared: Option<Vec<MetaResult>>,
+    // shared with the `LedgerBatch` returned by the last preparation.
+    prepared: Option<Arc<Vec<MetaResult>>>,
 
     process: Option<Child>,
 
     bounded_buffer_size: Option<usize>,
 
-    staggered: Option<u32>,
+    stagger_step_size: Option<u32>,
+
+    metrics: Arc<IngestionMetrics>,
+
+    http_port: Option<u16>,
+
+    history_archive_headers: Vec<(String, String)>,
+
+    catchup_limits: CatchupLimits,
+
+    // kept to regenerate the config, see `CaptiveCore::update_config`.
+    validators: Vec<ValidatorEntry>,
+
+    quorum_set: Option<QuorumSetConfig>,
+
+    check_archive_before_catchup: bool,
+
+    log_level: CoreLogLevel,
+
+    // set while a background thread (watchdog or sequential catchup
+    // coordinator) owns the stellar-core process.
+    detached_shutdown: Option<Arc<AtomicBool>>,
 }
 
 /// Represents the potential errors that can occur during runner operations.
@@ -70,20 +155,70 @@ pub enum RunnerError {
     /// An attempt was made to kill a process, but no process was found.
     #[error("Asked to kill process, but no process was found")]
     ProcessNotFound,
+
+    /// The stellar-core process exited unsuccessfully on its own, e.g.
+    /// `1` for a bad configuration or `137` when it was OOM-killed.
+    #[error("stellar-core process exited with code {0}")]
+    ProcessExitedWithCode(i32),
+
+    /// The output of `stellar-core --version` doesn't contain a version.
+    #[error("Could not find the stellar-core version in {0:?}")]
+    UnknownCoreVersion(String),
+
+    /// The history archives at the given URLs couldn't be reached.
+    #[error("History archive unreachable: {0}")]
+    ArchiveUnreachable(String),
+
+    /// Error encountered while querying stellar-core's HTTP API.
+    #[cfg(feature = "http-api")]
+    #[error("HTTP API error: {0}")]
+    HttpApi(String),
+}
+
+// the archive URLs of the `HISTORY` and `get` commands of a stellar-core
+// config, e.g. `https://history.stellar.org/prd/core-live/core_live_001`.
+fn history_archive_urls(config: &str) -> Vec<String> {
+    config
+        .lines()
+        .filter(|line| line.starts_with("HISTORY=") || line.starts_with("get="))
+        .flat_map(|line| line.split(|c: char| c.is_whitespace() || c == '"'))
+        .filter(|word| word.starts_with("http://") || word.starts_with("https://"))
+        .map(|url| url.trim_end_matches("/{0}").to_string())
+        .collect()
+}
+
+// like a shell, reports processes terminated by a signal as 128 + signal.
+#[cfg(unix)]
+fn exit_code(status: ExitStatus) -> i32 {
+    use std::os::unix::process::ExitStatusExt;
+
+    status
+        .code()
+        .or_else(|| status.signal().map(|signal| 128 + signal))
+        .unwrap_or(-1)
+}
+
+#[cfg(not(unix))]
+fn exit_code(status: ExitStatus) -> i32 {
+    status.code().unwrap_or(-1)
 }
 
 impl StellarCoreRunner {
     fn run_core_cli(&mut self, args: &[&str]) -> Result<(), RunnerError> {
-        let conf_arg = format!("--conf {}/stellar-core.cfg", self.context_path);
+        let conf_path = format!("{}/stellar-core.cfg", self.context_path);
 
         let mut cmd = Command::new(&self.executable_path);
         for arg in args {
             cmd.arg(arg);
         }
+        // options and their values are separate arguments, as expected
+        // outside of POSIX shells.
         cmd.current_dir(&self.context_path)
-            .arg(conf_arg)
+            .arg("--conf")
+            .arg(conf_path)
             //.arg("--in-memory") // TODO: manage in-memory or DB running on implementor choice.
-            .arg("--ll INFO");
+            .arg("--ll")
+            .arg(self.log_level.as_str());
 
         let cmd = cmd.stdout(std::process::Stdio::piped()).spawn();
 
@@ -98,13 +233,21 @@ impl StellarCoreRunner {
     }
 
     fn kill_process(&mut self) -> Result<(), RunnerError> {
-        if let Some(child) = self.process.as_mut() {
+        if let Some(mut child) = self.process.take() {
+            if let Some(status) = child.try_wait()? {
+                return if status.success() {
+                    Ok(())
+                } else {
+                    Err(RunnerError::ProcessExitedWithCode(exit_code(status)))
+                };
+            }
+
             child.kill()?;
-            self.process = None;
+            child.wait()?;
 
             Ok(())
         } else {
-            if let Some(_) = self.staggered {
+            if let Some(_) = self.stagger_step_size {
                 Ok(())
             } else {
                 Err(RunnerError::ProcessNotFound)
@@ -112,12 +255,38 @@ impl StellarCoreRunner {
         }
     }
 
-    fn remove_temp_data(&self) -> Result<(), RunnerError> {
-        let mut cmd = Command::new("rm");
-        cmd.arg("-rf")
-            .arg("buckets")
-            .current_dir(&self.context_path)
-            .spawn()?;
+    /// Stops the stellar-core process, if any, without removing its data.
+    /// Detached background runners are signaled to shut down.
+    pub(crate) fn stop_process(&mut self) {
+        if let Some(shutdown) = self.detached_shutdown.take() {
+            shutdown.store(true, Ordering::Relaxed);
+        }
+
+        if self.process.is_some() {
+            let _ = self.kill_process();
+        }
+
+        self.set_status(RunnerStatus::Closed);
+    }
+
+    pub(crate) fn remove_temp_data(&self) -> Result<(), RunnerError> {
+        let context = Path::new(&self.context_path);
+        let buckets = fs::remove_dir_all(context.join("buckets"));
+
+        let database = context.join("stellar.db");
+        let database = if database.is_dir() {
+            fs::remove_dir_all(database)
+        } else {
+            fs::remove_file(database)
+        };
+
+        // like `rm -rf`, data that was never created isn't an error.
+        for result in [buckets, database] {
+            match result {
+                Err(error) if error.kind() != io::ErrorKind::NotFound => return Err(error.into()),
+                _ => (),
+            }
+        }
 
         Ok(())
     }
@@ -127,21 +296,214 @@ impl StellarCoreRunner {
     }
 
     fn load_prepared(&mut self) -> Result<(), RunnerError> {
-        match self.ledger_buffer_reader.as_ref().unwrap().read_meta() {
+        match self.ledger_buffer_reader.as_mut().unwrap().drain() {
             Ok(ledgers_meta) => {
-                self.prepared = Some(ledgers_meta);
+                self.prepared = Some(Arc::new(ledgers_meta));
                 Ok(())
             }
             Err(error) => Err(RunnerError::MetaReader(error)),
         }
     }
 
+    /// Creates a closed runner sharing this runner's configuration
+    /// but using a different context directory.
+    pub(crate) fn with_context_path(&self, context_path: String) -> Self {
+        Self {
+            executable_path: self.executable_path.clone(),
+            context_path,
+            status: RunnerStatus::Closed,
+            status_tx: Arc::new(watch::channel(RunnerStatus::Closed).0),
+            ledger_buffer_reader: None,
+            prepared: None,
+            process: None,
+            bounded_buffer_size: self.bounded_buffer_size,
+            stagger_step_size: self.stagger_step_size,
+            metrics: self.metrics.clone(),
+            http_port: self.http_port,
+            history_archive_headers: self.history_archive_headers.clone(),
+            catchup_limits: self.catchup_limits.clone(),
+            validators: self.validators.clone(),
+            quorum_set: self.quorum_set.clone(),
+            check_archive_before_catchup: self.check_archive_before_catchup,
+            log_level: self.log_level,
+            detached_shutdown: None,
+        }
+    }
+
+    pub(crate) fn metrics(&self) -> Arc<IngestionMetrics> {
+        self.metrics.clone()
+    }
+
+    pub(crate) fn context_path(&self) -> &str {
+        &self.context_path
+    }
+
+    pub(crate) fn http_port(&self) -> Option<u16> {
+        self.http_port
+    }
+
+    pub(crate) fn history_archive_headers(&self) -> &[(String, String)] {
+        &self.history_archive_headers
+    }
+
+    pub(crate) fn catchup_limits(&self) -> &CatchupLimits {
+        &self.catchup_limits
+    }
+
+    pub(crate) fn validators(&self) -> &[ValidatorEntry] {
+        &self.validators
+    }
+
+    pub(crate) fn quorum_set(&self) -> Option<&QuorumSetConfig> {
+        self.quorum_set.as_ref()
+    }
+
+    /// Gets the sequence of the last ledger closed by the running stellar-core
+    /// from the `/info` endpoint of its HTTP API, e.g. to compute the lag of
+    /// online ingestion without reading the metadata stream.
+    ///
+    /// # Returns
+    ///
+    /// Returns the ledger sequence, or `RunnerError::HttpApi` if `http_port`
+    /// isn't configured or the request fails.
+    #[cfg(feature = "http-api")]
+    pub async fn get_latest_closed_ledger_via_http(&self) -> Result<u32, RunnerError> {
+        let port = self
+            .http_port
+            .ok_or_else(|| RunnerError::HttpApi("HTTP port is disabled".to_string()))?;
+        let url = format!("http://localhost:{}/info", port);
+
+        let info: serde_json::Value = tokio::task::spawn_blocking(move || {
+            ureq::get(&url)
+                .call()
+                .map_err(|error| error.to_string())?
+                .into_json()
+                .map_err(|error| error.to_string())
+        })
+        .await
+        .map_err(|error| RunnerError::HttpApi(error.to_string()))?
+        .map_err(RunnerError::HttpApi)?;
+
+        info["info"]["ledger"]["num"]
+            .as_u64()
+            .map(|sequence| sequence as u32)
+            .ok_or_else(|| RunnerError::HttpApi(format!("No ledger in {}", info)))
+    }
+
+    pub(crate) fn prepared(&self) -> Option<&Vec<MetaResult>> {
+        self.prepared.as_deref()
+    }
+
+    pub(crate) fn shared_prepared(&self) -> Option<Arc<Vec<MetaResult>>> {
+        self.prepared.clone()
+    }
+
+    pub(crate) fn set_prepared(&mut self, prepared: Vec<MetaResult>) {
+        self.prepared = Some(Arc::new(prepared))
+    }
+
     // This function is not yet used anywhere in the codebase but might be in the future.
     #[allow(dead_code)]
     pub(crate) fn status(&self) -> &RunnerStatus {
         &self.status
     }
 
+    /// Detects the version of the stellar-core executable by running
+    /// `stellar-core --version`.
+    ///
+    /// # Returns
+    ///
+    /// Returns the first semantic version in the output (e.g. `22.1.0` from
+    /// `v22.1.0`), or a `RunnerError` if the executable can't be run or
+    /// reports no version.
+    pub fn detect_version(&self) -> Result<semver::Version, RunnerError> {
+        let output = Command::new(&self.executable_path)
+            .arg("--version")
+            .output()?;
+        let output = String::from_utf8_lossy(&output.stdout);
+
+        output
+            .split_whitespace()
+            .find_map(|word| semver::Version::parse(word.trim_start_matches('v')).ok())
+            .ok_or_else(|| RunnerError::UnknownCoreVersion(output.trim().to_string()))
+    }
+
+    /// Checks that the history archives of the generated config are reachable
+    /// by running `stellar-core report-last-history-checkpoint`, which fetches
+    /// the latest history archive state from them.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if the archives are reachable, or
+    /// `RunnerError::ArchiveUnreachable` with the archive URLs if the check fails.
+    pub fn check_archive_connectivity(&self) -> Result<(), RunnerError> {
+        let conf_path = format!("{}/stellar-core.cfg", self.context_path);
+
+        let output = Command::new(&self.executable_path)
+            .current_dir(&self.context_path)
+            .arg("report-last-history-checkpoint")
+            .arg("--conf")
+            .arg(&conf_path)
+            .arg("--ll")
+            .arg("ERROR")
+            .output()?;
+
+        if output.status.success() {
+            return Ok(());
+        }
+
+        let config = std::fs::read_to_string(&conf_path)?;
+        Err(RunnerError::ArchiveUnreachable(
+            history_archive_urls(&config).join(", "),
+        ))
+    }
+
+    fn set_status(&mut self, status: RunnerStatus) {
+        self.status = status;
+        self.status_tx.send_replace(status);
+    }
+
+    // Whether the runner can be started. A detached background job that ends
+    // on its own publishes `Closed` without going through `close_runner`.
+    fn is_closed(&mut self) -> bool {
+        if self.detached_shutdown.is_some() && *self.status_tx.borrow() == RunnerStatus::Closed {
+            self.status = RunnerStatus::Closed;
+            self.detached_shutdown = None;
+        }
+
+        self.status == RunnerStatus::Closed
+    }
+
+    /// Subscribes to the status of the runner.
+    ///
+    /// The receiver starts with the current status, and `changed()` resolves
+    /// on every following transition (e.g. `Closed` to `RunningOffline`).
+    /// When running with a watchdog the `Restarting` transitions of the
+    /// restarted process are published too.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `watch::Receiver` of the runner status.
+    pub fn status_watch(&self) -> watch::Receiver<RunnerStatus> {
+        self.status_tx.subscribe()
+    }
+
+    /// Returns whether the multi-thread mode channel of the runner is bounded,
+    /// i.e. whether `IngestionConfig::bounded_buffer_size` is set.
+    pub fn is_bounded_channel(&self) -> bool {
+        self.bounded_buffer_size.is_some()
+    }
+
+    /// Returns the capacity of the multi-thread mode channel of the runner,
+    /// or `None` if the channel is unbounded.
+    pub fn bounded_channel_capacity(&self) -> Option<usize> {
+        self.bounded_buffer_size
+    }
+
+    pub(crate) fn stagger_step_size(&self) -> Option<u32> {
+        self.stagger_step_size
+    }
+
     pub(crate) fn thread_mode(&self) -> Option<&BufferedLedgerMetaReaderMode> {
         if let Some(mode) = self.ledger_buffer_reader.as_ref() {
             Some(mode.thread_mode())
@@ -160,6 +522,17 @@ pub trait StellarCoreRunnerPublic {
     /// Performs catchup using a single thread, processing data from the specified range.
     fn catchup_single_thread(&mut self, from: u32, to: u32) -> Result<(), RunnerError>;
 
+    /// Performs catchup using a single thread, processing data from the specified range
+    /// and calling `on_ledger` with the sequence of each ledger as it is decoded.
+    fn catchup_single_thread_with_progress<F>(
+        &mut self,
+        from: u32,
+        to: u32,
+        on_ledger: F,
+    ) -> Result<(), RunnerError>
+    where
+        F: FnMut(u32);
+
     /// Performs catchup using multiple threads, processing data from the specified range.
     /// Returns a channel receiver for receiving metadata results.
     fn catchup_multi_thread(
@@ -169,6 +542,7 @@ pub trait StellarCoreRunnerPublic {
     ) -> Result<Receiver<Box<MetaResult>>, RunnerError>;
 
     /// Starts the runner and returns a channel receiver for receiving metadata results.
+    /// Returns a `RunnerError` if the initial quick catchup or the run can't be started.
     fn run(&mut self) -> Result<Receiver<Box<MetaResult>>, RunnerError>;
 
     /// Reads the prepared metadata results from the runner.
@@ -184,20 +558,51 @@ impl StellarCoreRunnerPublic for StellarCoreRunner {
             executable_path: config.executable_path,
             context_path: config.context_path.0,
             status: RunnerStatus::Closed,
+            status_tx: Arc::new(watch::channel(RunnerStatus::Closed).0),
             ledger_buffer_reader: None,
             prepared: None,
             process: None,
             bounded_buffer_size: config.bounded_buffer_size,
-            staggered: config.staggered,
+            // a step of 0 ledgers can't split the range, so it's treated as unset.
+            stagger_step_size: config.stagger_step_size.filter(|step| *step > 0),
+            metrics: Arc::new(IngestionMetrics::new()),
+            http_port: config.http_port,
+            history_archive_headers: config.history_archive_headers,
+            catchup_limits: CatchupLimits {
+                catchup_complete: config.catchup_complete,
+                catchup_recent: config.catchup_recent,
+                max_slots_to_remember: config.max_slots_to_remember,
+            },
+            validators: config.validators,
+            quorum_set: config.quorum_set,
+            check_archive_before_catchup: config.check_archive_before_catchup,
+            log_level: config.log_level,
+            detached_shutdown: None,
         }
     }
 
     fn catchup_single_thread(&mut self, from: u32, to: u32) -> Result<(), RunnerError> {
-        if self.status != RunnerStatus::Closed {
+        self.catchup_single_thread_with_progress(from, to, |_| ())
+    }
+
+    fn catchup_single_thread_with_progress<F>(
+        &mut self,
+        from: u32,
+        to: u32,
+        mut on_ledger: F,
+    ) -> Result<(), RunnerError>
+    where
+        F: FnMut(u32),
+    {
+        if !self.is_closed() {
             return Err(RunnerError::AlreadyRunning);
         }
 
-        self.status = RunnerStatus::RunningOffline;
+        if self.check_archive_before_catchup {
+            self.check_archive_connectivity()?;
+        }
+
+        self.set_status(RunnerStatus::RunningOffline);
 
         let range = format!("{}/{}", to, to - from + 1);
 
@@ -205,7 +610,8 @@ impl StellarCoreRunnerPublic for StellarCoreRunner {
             "catchup",
             "--in-memory",
             &range,
-            "--metadata-output-stream fd:1",
+            "--metadata-output-stream",
+            "fd:1",
         ])?;
         let stdout = self.process.as_mut().unwrap().stdout.take().unwrap(); // TODO: handle panic
 
@@ -216,15 +622,22 @@ impl StellarCoreRunnerPublic for StellarCoreRunner {
         //.filter_map(|line| line.ok())
         //.for_each(|line| println!("{}", line));
 
+        // the catchup also replays the ledgers from the start of the checkpoint,
+        // so the cache is sized for the range plus a checkpoint to avoid
+        // reallocating it during long catchups.
+        let count_hint = (to - from + 1) as usize + CHECKPOINT_FREQUENCY as usize;
+
         let ledger_buffer_reader = match BufferedLedgerMetaReader::new(
             BufferedLedgerMetaReaderMode::SingleThread,
             Box::new(reader),
             None,
             None,
             None,
-                None
+            None,
+            None,
+            Some(count_hint),
         ) {
-            Ok(reader) => reader,
+            Ok(reader) => reader.with_metrics(self.metrics.clone()),
             Err(error) => return Err(RunnerError::MetaReader(error)),
         };
         self.ledger_buffer_reader = Some(ledger_buffer_reader);
@@ -232,7 +645,7 @@ impl StellarCoreRunnerPublic for StellarCoreRunner {
         self.ledger_buffer_reader
             .as_mut()
             .unwrap()
-            .single_thread_read_ledger_meta_from_pipe()?;
+            .single_thread_read_ledger_meta_from_pipe_with_progress(&mut on_ledger)?;
 
         self.load_prepared()?;
 
@@ -247,13 +660,17 @@ impl StellarCoreRunnerPublic for StellarCoreRunner {
         from: u32,
         to: u32,
     ) -> Result<Receiver<Box<MetaResult>>, RunnerError> {
-        if self.status != RunnerStatus::Closed {
+        if !self.is_closed() {
             return Err(RunnerError::AlreadyRunning);
         }
 
-        self.status = RunnerStatus::RunningOffline;
+        if self.check_archive_before_catchup {
+            self.check_archive_connectivity()?;
+        }
+
+        self.set_status(RunnerStatus::RunningOffline);
 
-        if let Some(stagger_every) = self.staggered {
+        if let Some(stagger_every) = self.stagger_step_size {
             let ledgers_amount = to - from;
             let stagger_times = ledgers_amount / stagger_every;
 
@@ -263,7 +680,8 @@ impl StellarCoreRunnerPublic for StellarCoreRunner {
                     "catchup",
                     "--in-memory",
                     &range,
-                    "--metadata-output-stream fd:1",
+                    "--metadata-output-stream",
+                    "fd:1",
                 ])?;
                 let stdout = self.process.as_mut().unwrap().stdout.take().unwrap(); // TODO: handle panic
 
@@ -281,6 +699,8 @@ impl StellarCoreRunnerPublic for StellarCoreRunner {
                     let cloned = transmitter.clone();
                     let context_path = self.context_path.clone();
                     let executable_path = self.executable_path.clone();
+                    let log_level = self.log_level;
+                    let metrics = self.metrics.clone();
 
                     let step = (to - from + 1) / stagger_times;
                     let ranges: Vec<_> = (0..stagger_times)
@@ -301,10 +721,12 @@ impl StellarCoreRunnerPublic for StellarCoreRunner {
                                     "catchup",
                                     "--in-memory",
                                     &range,
-                                    "--metadata-output-stream fd:1",
+                                    "--metadata-output-stream",
+                                    "fd:1",
                                 ],
                                 &context_path,
                                 &executable_path,
+                                log_level,
                             )?;
                             let stdout = process.stdout.unwrap();
                             let reader = BufReader::new(stdout);
@@ -317,9 +739,11 @@ impl StellarCoreRunnerPublic for StellarCoreRunner {
                                         None,
                                         Some(cloned.clone()),
                                         None,
-                None
+                                        None,
+                                        None,
+                                        None
                                     ) {
-                                        Ok(reader) => reader,
+                                        Ok(reader) => reader.with_metrics(metrics.clone()),
                                         Err(error) => return Err(RunnerError::MetaReader(error)),
                                     };
 
@@ -343,6 +767,8 @@ impl StellarCoreRunnerPublic for StellarCoreRunner {
                     let cloned = transmitter.clone();
                     let context_path = self.context_path.clone();
                     let executable_path = self.executable_path.clone();
+                    let log_level = self.log_level;
+                    let metrics = self.metrics.clone();
 
                     let step = (to - from + 1) / stagger_times;
                     let ranges: Vec<_> = (0..stagger_times)
@@ -362,10 +788,12 @@ impl StellarCoreRunnerPublic for StellarCoreRunner {
                                     "catchup",
                                     "--in-memory",
                                     &range,
-                                    "--metadata-output-stream fd:1",
+                                    "--metadata-output-stream",
+                                    "fd:1",
                                 ],
                                 &context_path,
                                 &executable_path,
+                                log_level,
                             )?;
                             let stdout = process.stdout.unwrap();
                             let reader = BufReader::new(stdout);
@@ -378,9 +806,11 @@ impl StellarCoreRunnerPublic for StellarCoreRunner {
                                         Some(cloned.clone()),
                                         None,
                                         None,
-                None
+                                        None,
+                                        None,
+                                        None
                                     ) {
-                                        Ok(reader) => reader,
+                                        Ok(reader) => reader.with_metrics(metrics.clone()),
                                         Err(error) => return Err(RunnerError::MetaReader(error)),
                                     };
 
@@ -407,7 +837,8 @@ impl StellarCoreRunnerPublic for StellarCoreRunner {
                 "catchup",
                 "--in-memory",
                 &range,
-                "--metadata-output-stream fd:1",
+                "--metadata-output-stream",
+                "fd:1",
             ])?;
             let stdout = self.process.as_mut().unwrap().stdout.take().unwrap(); // TODO: handle panic
 
@@ -422,11 +853,11 @@ impl StellarCoreRunnerPublic for StellarCoreRunner {
     }
 
     fn run(&mut self) -> Result<Receiver<Box<MetaResult>>, RunnerError> {
-        if self.status != RunnerStatus::Closed {
+        if !self.is_closed() {
             return Err(RunnerError::AlreadyRunning);
         }
 
-        self.status = RunnerStatus::RunningOnline;
+        self.set_status(RunnerStatus::RunningOnline);
 
         // Creating/resetting the DB and a quick catchup.
         // TODO: optimize this process by checking what's the
@@ -436,11 +867,14 @@ impl StellarCoreRunnerPublic for StellarCoreRunner {
             //self.run_core_cli(&["new-db"])?;
             //self.process.as_mut().unwrap().wait().unwrap();
 
-            let _ = self.run_core_cli(&["catchup", "current/2"]);
-            self.process.as_mut().unwrap().wait().unwrap();
+            self.run_core_cli(&["catchup", "current/2"])?;
+            self.process
+                .as_mut()
+                .ok_or(RunnerError::ProcessNotFound)?
+                .wait()?;
         }
 
-        self.run_core_cli(&["run", "--metadata-output-stream fd:1"])?;
+        self.run_core_cli(&["run", "--metadata-output-stream", "fd:1"])?;
         let stdout = self.process.as_mut().unwrap().stdout.take().unwrap(); // TODO: handle panic;
 
         let reader = BufReader::new(stdout);
@@ -453,7 +887,7 @@ impl StellarCoreRunnerPublic for StellarCoreRunner {
     }
 
     fn read_prepared(&self) -> Vec<MetaResult> {
-        self.prepared.as_ref().unwrap().clone()
+        self.prepared.as_deref().unwrap().clone()
     }
 
     fn close_runner(&mut self) -> Result<(), RunnerError> {
@@ -461,176 +895,638 @@ impl StellarCoreRunnerPublic for StellarCoreRunner {
             return Err(RunnerError::AlreadyRunning);
         }
 
-        self.status = RunnerStatus::Closed;
+        self.set_status(RunnerStatus::Closed);
 
-        self.kill_process()?;
+        // the background thread owns the process and cleans up after itself.
+        if let Some(shutdown) = self.detached_shutdown.take() {
+            shutdown.store(true, Ordering::Relaxed);
+            return Ok(());
+        }
+
+        // still clean up when the process had already exited
+        // unsuccessfully, then report its exit code.
+        let exited = match self.kill_process() {
+            Err(RunnerError::ProcessExitedWithCode(code)) => Some(code),
+            result => {
+                result?;
+                None
+            }
+        };
         self.remove_temp_data()?;
         self.reset_bufreader();
 
-        Ok(())
+        match exited {
+            Some(code) => Err(RunnerError::ProcessExitedWithCode(code)),
+            None => Ok(()),
+        }
     }
 }
 
 impl StellarCoreRunner {
-    pub async fn async_catchup_multi_thread(
+    /// Starts the runner online and sends every ledger to `subscriber_count`
+    /// receivers.
+    ///
+    /// A fan-out thread clones each result into one bounded channel per
+    /// subscriber, sized by `bounded_buffer_size` or [`LEDGER_READ_AHEAD_BUFFER_SIZE`]
+    /// if unset. Since sends block while a channel is full, a slow subscriber
+    /// stalls every other subscriber and the meta pipe. Dropped subscribers
+    /// stop receiving and the fan-out ends once all of them are dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `subscriber_count` - The amount of receivers to create.
+    ///
+    /// # Returns
+    ///
+    /// Returns the receivers, or a `RunnerError` if the runner can't be started.
+    pub fn run_broadcast(
+        &mut self,
+        subscriber_count: usize,
+    ) -> Result<Vec<Receiver<Box<MetaResult>>>, RunnerError> {
+        let receiver = self.run()?;
+        let bound = self
+            .bounded_buffer_size
+            .unwrap_or(LEDGER_READ_AHEAD_BUFFER_SIZE);
+
+        let (mut transmitters, receivers): (Vec<_>, Vec<_>) = (0..subscriber_count)
+            .map(|_| std::sync::mpsc::sync_channel(bound))
+            .unzip();
+
+        thread::spawn(move || {
+            for result in receiver.iter() {
+                transmitters.retain(|transmitter| transmitter.send(result.clone()).is_ok());
+
+                if transmitters.is_empty() {
+                    logging::info!("All broadcast subscribers dropped, stopping fan-out");
+                    break;
+                }
+            }
+        });
+
+        Ok(receivers)
+    }
+
+    /// Catches up multiple independent ranges one after another, sending all
+    /// their ledgers through the same receiver.
+    ///
+    /// A coordinator thread runs one catchup subprocess per range, in order,
+    /// closing each subprocess and removing its temporary data before starting
+    /// the next one. Unlike staggered mode, every range is caught up on its own.
+    ///
+    /// Note that the results of each range may end with a catchup-end sentinel
+    /// (see [`MetaResult::is_catchup_end_sentinel`]), so callers must not stop
+    /// at the first sentinel. The receiver is closed once the last range is done, or
+    /// early if a later range fails to start, in which case the error is logged.
+    ///
+    /// # Arguments
+    ///
+    /// * `ranges` - The ranges to catch up, in order.
+    ///
+    /// # Returns
+    ///
+    /// Returns a channel receiver for receiving metadata results, or a `RunnerError`
+    /// if the first catchup fails to start.
+    pub fn sequential_catchup_multi_thread(
+        &mut self,
+        ranges: Vec<BoundedRange>,
+    ) -> Result<Receiver<Box<MetaResult>>, RunnerError> {
+        if !self.is_closed() {
+            return Err(RunnerError::AlreadyRunning);
+        }
+
+        let mut ranges = ranges.into_iter();
+        let mut runner = self.with_context_path(self.context_path.clone());
+        let mut inner_receiver = match ranges.next() {
+            Some(range) => runner.catchup_multi_thread(range.0, range.1)?,
+            None => return Ok(std::sync::mpsc::channel().1),
+        };
+
+        let shutdown = Arc::new(AtomicBool::new(false));
+        self.detached_shutdown = Some(shutdown.clone());
+        self.set_status(RunnerStatus::RunningOffline);
+
+        let (transmitter, receiver) = std::sync::mpsc::channel();
+        thread::spawn(move || loop {
+            for result in inner_receiver.iter() {
+                if transmitter.send(result).is_err() {
+                    shutdown.store(true, Ordering::Relaxed);
+                    break;
+                }
+            }
+
+            let _ = runner.close_runner();
+
+            let next = match ranges.next() {
+                Some(range) if !shutdown.load(Ordering::Relaxed) => range,
+                _ => break,
+            };
+
+            match runner.catchup_multi_thread(next.0, next.1) {
+                Ok(next_receiver) => inner_receiver = next_receiver,
+                Err(error) => {
+                    logging::error!("Failed to start catchup of {:?}: {}", next, error);
+                    break;
+                }
+            }
+        });
+
+        Ok(receiver)
+    }
+
+    /// Catches up the last `lookback_ledgers` ledgers and then starts the runner
+    /// online, sending the ledgers of both through the same receiver.
+    ///
+    /// Unlike the quick catchup of `run`, the catchup's ledgers are sent to the
+    /// receiver, and `run` then resumes from the last caught up ledger. A
+    /// coordinator thread switches between the two subprocesses, skipping
+    /// ledgers that were already sent, so that sequences are received in order.
+    /// The end of the catchup sentinel is not forwarded.
+    ///
+    /// # Arguments
+    ///
+    /// * `lookback_ledgers` - The amount of ledgers up to the current one to catch up.
+    ///
+    /// # Returns
+    ///
+    /// Returns a channel receiver for receiving metadata results, or a `RunnerError`
+    /// if the catchup fails to start.
+    pub fn run_with_initial_catchup(
+        &mut self,
+        lookback_ledgers: u32,
+    ) -> Result<Receiver<Box<MetaResult>>, RunnerError> {
+        if !self.is_closed() {
+            return Err(RunnerError::AlreadyRunning);
+        }
+
+        let mut runner = self.with_context_path(self.context_path.clone());
+        runner.status = RunnerStatus::RunningOffline;
+
+        // not in-memory, so that `run` resumes from the caught up ledgers.
+        let catchup = format!("current/{}", lookback_ledgers);
+        let mut inner_receiver =
+            runner.stream_core_cli(&["catchup", &catchup, "--metadata-output-stream", "fd:1"])?;
+
+        let shutdown = Arc::new(AtomicBool::new(false));
+        self.detached_shutdown = Some(shutdown.clone());
+        self.set_status(RunnerStatus::RunningOnline);
+
+        let (transmitter, receiver) = std::sync::mpsc::channel();
+        thread::spawn(move || {
+            let mut last_sequence: Option<u32> = None;
+            let mut online = false;
+
+            loop {
+                loop {
+                    let result = match inner_receiver.recv_timeout(WATCHDOG_POLL_INTERVAL) {
+                        Ok(result) => result,
+                        Err(RecvTimeoutError::Timeout) if !shutdown.load(Ordering::Relaxed) => {
+                            continue
+                        }
+                        Err(_) => break,
+                    };
+
+                    let catchup_end = !online && result.is_catchup_end_sentinel();
+
+                    match LedgerCloseMetaReader::ledger_sequence(&result) {
+                        Ok(sequence) if last_sequence.is_some_and(|last| sequence <= last) => {
+                            continue
+                        }
+                        Ok(sequence) => last_sequence = Some(sequence),
+                        Err(_) if catchup_end => continue,
+                        Err(_) => (),
+                    }
+
+                    if transmitter.send(result).is_err() {
+                        shutdown.store(true, Ordering::Relaxed);
+                        break;
+                    }
+                }
+
+                if online || shutdown.load(Ordering::Relaxed) {
+                    let _ = runner.close_runner();
+                    break;
+                }
+
+                // the catchup pipe is closed once the catchup is done.
+                if let Some(mut process) = runner.process.take() {
+                    let _ = process.wait();
+                }
+                runner.reset_bufreader();
+                runner.status = RunnerStatus::RunningOnline;
+
+                match runner.stream_core_cli(&["run", "--metadata-output-stream", "fd:1"]) {
+                    Ok(next_receiver) => {
+                        inner_receiver = next_receiver;
+                        online = true;
+                    }
+                    Err(error) => {
+                        logging::error!("Failed to start running after catchup: {}", error);
+                        let _ = runner.close_runner();
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok(receiver)
+    }
+
+    // Runs a stellar-core command and starts reading its metadata output stream.
+    fn stream_core_cli(&mut self, args: &[&str]) -> Result<Receiver<Box<MetaResult>>, RunnerError> {
+        self.run_core_cli(args)?;
+        let stdout = self.process.as_mut().unwrap().stdout.take().unwrap(); // TODO: handle panic
+
+        let reader = BufReader::new(stdout);
+
+        if let Some(bound) = self.bounded_buffer_size {
+            self.start_and_sync_transmitter(reader, bound)
+        } else {
+            self.start_and_transmitter(reader)
+        }
+    }
+
+    /// Starts the runner online and keeps it running, restarting stellar-core
+    /// whenever its process exits.
+    ///
+    /// A watchdog thread monitors the process and, once it exits, waits for
+    /// `restart_delay` before running it again. Ledgers from every run are sent
+    /// through the same returned receiver, so restarts only show as a gap in
+    /// the received sequences. The watchdog stops when the runner is closed
+    /// or the receiver is dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `restart_delay` - Time to wait before restarting an exited process.
+    ///
+    /// # Returns
+    ///
+    /// Returns a channel receiver for receiving metadata results, or a `RunnerError`
+    /// if the first run fails.
+    pub fn run_with_watchdog(
+        &mut self,
+        restart_delay: Duration,
+    ) -> Result<Receiver<Box<MetaResult>>, RunnerError> {
+        if !self.is_closed() {
+            return Err(RunnerError::AlreadyRunning);
+        }
+
+        let mut runner = self.with_context_path(self.context_path.clone());
+        runner.status_tx = self.status_tx.clone();
+        let mut inner_receiver = runner.run()?;
+
+        let shutdown = Arc::new(AtomicBool::new(false));
+        self.detached_shutdown = Some(shutdown.clone());
+        self.set_status(RunnerStatus::RunningOnline);
+
+        let (transmitter, receiver) = std::sync::mpsc::channel();
+        thread::spawn(move || loop {
+            let restart = match inner_receiver.recv_timeout(WATCHDOG_POLL_INTERVAL) {
+                Ok(result) => {
+                    if transmitter.send(result).is_err() {
+                        shutdown.store(true, Ordering::Relaxed);
+                    }
+                    false
+                }
+
+                Err(RecvTimeoutError::Timeout) => {
+                    if runner.process_exited() {
+                        // forward what's left in the pipe before restarting.
+                        for result in inner_receiver.iter() {
+                            if transmitter.send(result).is_err() {
+                                shutdown.store(true, Ordering::Relaxed);
+                                break;
+                            }
+                        }
+                        true
+                    } else {
+                        false
+                    }
+                }
+
+                Err(RecvTimeoutError::Disconnected) => true,
+            };
+
+            if shutdown.load(Ordering::Relaxed) {
+                let _ = runner.close_runner();
+                break;
+            }
+
+            if restart {
+                logging::warning!(
+                    "stellar-core process exited, restarting in {:?}",
+                    restart_delay
+                );
+                runner.set_status(RunnerStatus::Restarting);
+
+                let _ = runner.kill_process();
+                let _ = runner.remove_temp_data();
+                runner.reset_bufreader();
+
+                loop {
+                    thread::sleep(restart_delay);
+
+                    if shutdown.load(Ordering::Relaxed) {
+                        return;
+                    }
+
+                    // not published, subscribers keep seeing `Restarting`
+                    // until the process is running again.
+                    runner.status = RunnerStatus::Closed;
+                    match runner.run() {
+                        Ok(new_receiver) => {
+                            inner_receiver = new_receiver;
+                            break;
+                        }
+                        Err(error) => {
+                            logging::error!("Failed to restart stellar-core: {}", error);
+                            let _ = runner.kill_process();
+                        }
+                    }
+                }
+            }
+        });
+
+        Ok(receiver)
+    }
+
+    /// Catches up the range `from..=to` like `catchup_multi_thread`, retrying
+    /// the whole catchup when the stellar-core process exits unsuccessfully,
+    /// e.g. because an archive returned 404 or timed out on a segment.
+    ///
+    /// Retry `n` (starting at 0) waits `backoff * 2^n` before running the
+    /// catchup again. Ledgers already sent by a failed attempt are not sent
+    /// again, and its read errors (e.g. the truncated frame left in the pipe)
+    /// are dropped. If all retries fail, the read errors of the last attempt
+    /// are sent followed by a `BufReaderError::MaxRetriesExceeded` result
+    /// holding the last exit code, and the receiver is closed. The runner is
+    /// `Closed` again once the catchup ends, without calling `close_runner`.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - First ledger of the range.
+    /// * `to` - Last ledger of the range.
+    /// * `max_retries` - Maximum amount of times the catchup is retried.
+    /// * `backoff` - Time to wait before the first retry.
+    ///
+    /// # Returns
+    ///
+    /// Returns a channel receiver for receiving metadata results, or a `RunnerError`
+    /// if the first attempt can't be started.
+    pub fn catchup_multi_thread_with_retry(
         &mut self,
         from: u32,
         to: u32,
-        to_current: bool // note:this is a hotfix, more complete fix is todo.
-    ) -> Result<tokio::sync::mpsc::UnboundedReceiver<Box<MetaResult>>, RunnerError> {
-        if self.status != RunnerStatus::Closed {
+        max_retries: u32,
+        backoff: Duration,
+    ) -> Result<Receiver<Box<MetaResult>>, RunnerError> {
+        if !self.is_closed() {
             return Err(RunnerError::AlreadyRunning);
         }
 
-        self.status = RunnerStatus::RunningOffline;
+        let range = format!("{}/{}", to, to - from + 1);
 
-        if let Some(stagger_every) = self.staggered {
-            let ledgers_amount = to - from;
-            let stagger_times = ledgers_amount / stagger_every;
+        let mut runner = self.with_context_path(self.context_path.clone());
+        runner.status = RunnerStatus::RunningOffline;
+        let mut inner_receiver = runner.stream_core_cli(&[
+            "catchup",
+            "--in-memory",
+            &range,
+            "--metadata-output-stream",
+            "fd:1",
+        ])?;
 
-            let receiver = if stagger_times <= 1 {
-                let range = format!("{}/{}", to, to - from + 1); // note: staggering doesn't support current ledger catchups
-                self.run_core_cli(&[
+        let shutdown = Arc::new(AtomicBool::new(false));
+        self.detached_shutdown = Some(shutdown.clone());
+        self.set_status(RunnerStatus::RunningOffline);
+
+        let status_tx = self.status_tx.clone();
+        let (transmitter, receiver) = std::sync::mpsc::channel();
+        thread::spawn(move || {
+            let mut last_sequence: Option<u32> = None;
+            let mut attempt = 0;
+            let mut receiver_dropped = false;
+
+            loop {
+                let mut read_errors = Vec::new();
+
+                loop {
+                    let result = match inner_receiver.recv_timeout(WATCHDOG_POLL_INTERVAL) {
+                        Ok(result) => result,
+                        Err(RecvTimeoutError::Timeout) if !shutdown.load(Ordering::Relaxed) => {
+                            continue
+                        }
+                        Err(_) => break,
+                    };
+
+                    match LedgerCloseMetaReader::ledger_sequence(&result) {
+                        Ok(sequence) if last_sequence.is_some_and(|last| sequence <= last) => {
+                            continue
+                        }
+                        Ok(sequence) => last_sequence = Some(sequence),
+                        // held back until it's known whether the attempt is retried.
+                        Err(_) => {
+                            read_errors.push(result);
+                            continue;
+                        }
+                    }
+
+                    if transmitter.send(result).is_err() {
+                        receiver_dropped = true;
+                        break;
+                    }
+                }
+
+                if receiver_dropped || shutdown.load(Ordering::Relaxed) {
+                    let _ = runner.close_runner();
+                    break;
+                }
+
+                // the pipe is closed once the process is about to exit.
+                if let Some(process) = runner.process.as_mut() {
+                    let _ = process.wait();
+                }
+
+                let code = match runner.close_runner() {
+                    Err(RunnerError::ProcessExitedWithCode(code)) if attempt < max_retries => code,
+                    result => {
+                        let mut terminal_error = None;
+                        match result {
+                            Ok(()) => (),
+                            Err(RunnerError::ProcessExitedWithCode(exit_code)) => {
+                                let error = BufReaderError::MaxRetriesExceeded {
+                                    retries: max_retries,
+                                    exit_code,
+                                };
+                                logging::error!("{}", error);
+                                terminal_error = Some(error);
+                            }
+                            Err(error) => logging::error!("Failed to close the catchup: {}", error),
+                        }
+
+                        for result in read_errors {
+                            let _ = transmitter.send(result);
+                        }
+                        if let Some(error) = terminal_error {
+                            let _ = transmitter.send(Box::new(MetaResult {
+                                ledger_close_meta: None,
+                                err: Some(error),
+                            }));
+                        }
+                        break;
+                    }
+                };
+
+                let delay = backoff.saturating_mul(2u32.saturating_pow(attempt));
+                logging::warning!(
+                    "stellar-core catchup exited with code {}, retrying in {:?}",
+                    code,
+                    delay
+                );
+                thread::sleep(delay);
+                attempt += 1;
+
+                if shutdown.load(Ordering::Relaxed) {
+                    break;
+                }
+
+                runner.status = RunnerStatus::RunningOffline;
+                match runner.stream_core_cli(&[
                     "catchup",
+                    "--in-memory",
                     &range,
-                    "--metadata-output-stream fd:1",
-                ])?;
-                let stdout = self.process.as_mut().unwrap().stdout.take().unwrap(); // TODO: handle panic
+                    "--metadata-output-stream",
+                    "fd:1",
+                ]) {
+                    Ok(next_receiver) => inner_receiver = next_receiver,
+                    Err(error) => {
+                        logging::error!("Failed to restart the catchup: {}", error);
+                        let _ = runner.close_runner();
+                        break;
+                    }
+                }
+            }
 
-                let reader = BufReader::new(stdout);
-                self.start_and_transmitter_async(reader).await
-            } else {
-                let (transmitter, receiver) = tokio::sync::mpsc::unbounded_channel();
+            finish_detached(&status_tx, &shutdown);
+        });
+
+        Ok(receiver)
+    }
+
+    fn process_exited(&mut self) -> bool {
+        match self.process.as_mut() {
+            Some(child) => !matches!(child.try_wait(), Ok(None)),
+            None => true,
+        }
+    }
+}
+
+impl StellarCoreRunner {
+    /// Starts an async catchup of the range `from..=to`.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - First ledger of the range.
+    /// * `to` - Last ledger of the range.
+    /// * `to_current` - Whether the catchup should target the current ledger.
+    /// * `shutdown` - Optional cancellation signal. Once it's set to `true`
+    ///   the stellar-core process is killed and the receiver is closed.
+    ///   Closing the runner instead stops the catchup after the current
+    ///   stellar-core process exits.
+    ///
+    /// The pipe is read and the process is reaped on tokio's blocking thread
+    /// pool, so the catchup doesn't hold up a runtime worker. If a catchup
+    /// fails to start or to be read, the error is logged and the receiver is
+    /// closed. The runner is `Closed` again once the catchup ends.
+    ///
+    /// # Returns
+    ///
+    /// Returns a receiver for the metadata results, or a `RunnerError` if the
+    /// runner is already running.
+    pub async fn async_catchup_multi_thread(
+        &mut self,
+        from: u32,
+        to: u32,
+        to_current: bool, // note:this is a hotfix, more complete fix is todo.
+        shutdown: Option<watch::Receiver<bool>>,
+    ) -> Result<AsyncMetaReceiver, RunnerError> {
+        if !self.is_closed() {
+            return Err(RunnerError::AlreadyRunning);
+        }
 
-                let context_path = self.context_path.clone();
-                let executable_path = self.executable_path.clone();
+        self.set_status(RunnerStatus::RunningOffline);
 
+        let ranges = match self.stagger_step_size {
+            Some(stagger_every) if (to - from) / stagger_every > 1 => {
+                // note: staggering doesn't support current ledger catchups
+                let stagger_times = (to - from) / stagger_every;
                 let step = (to - from + 1) / stagger_times;
-                let ranges: Vec<_> = (0..stagger_times)
+
+                (0..stagger_times)
                     .map(|i| {
                         let start = from + i * step;
                         let end = std::cmp::min(start + step - 1, to);
-                        start..=end
+                        format!("{}/{}", end, end - start + 1)
                     })
-                    .collect();
-                
-                tokio::spawn(async move {
-                    for range in ranges {
-                        let range =
-                            format!("{}/{}", range.end(), range.end() - range.start() + 1);
-
-                        let process = run_core_cli(
-                            &[
-                                "catchup",
-                                &range,
-                                "--metadata-output-stream fd:1",
-                            ],
-                            &context_path,
-                            &executable_path,
-                        ).unwrap();
-
-                        let stdout = process.stdout.unwrap();
-                        let reader = BufReader::new(stdout);
-                        //let _ = Self::inner_start_from_pipe(reader, transmitter.clone()).await.unwrap();
-                        let mut stateless_ledger_buffer_reader = match BufferedLedgerMetaReader::new(
-                            BufferedLedgerMetaReaderMode::MultiThread,
-                            Box::new(reader),
-                            None,
-                            None,
-                            Some(transmitter.clone()),
-                            None
-                        ) {
-                            Ok(reader) => reader,
-                            Err(error) => return Err(RunnerError::MetaReader(error)),
-                        };
-            
-                        stateless_ledger_buffer_reader
-                            .async_multi_thread_read_ledger_meta_from_pipe()
-                            .await
-                            .unwrap()
-                    };
+                    .collect()
+            }
 
-                    Ok(())
-                });
+            Some(_) => vec![format!("{}/{}", to, to - from + 1)],
 
-                Ok(receiver)
-            };
-            
-            receiver
-        } else {
-            /*let range = if !to_current {
-                format!("{}/{}", to, to - from + 1)
-            } else {
-                format!("current/{}", to - from + 1)
-            };
+            None if to_current => vec![format!("current/{}", to - from + 1)],
 
-            self.run_core_cli(&[
-                "catchup",
-                &range,
-                "--metadata-output-stream fd:1",
-            ])?;
-            let stdout = self.process.as_mut().unwrap().stdout.take().unwrap(); // TODO: handle panic
+            None => vec![format!("{}/{}", to, to - from + 1)],
+        };
 
-            let reader = BufReader::new(stdout);
+        let (transmitter, bounded_transmitter, receiver) =
+            async_meta_channel(self.bounded_buffer_size);
 
-            self.start_and_transmitter_async(reader).await*/
-            let (transmitter, receiver) = tokio::sync::mpsc::unbounded_channel();
+        let context_path = self.context_path.clone();
+        let executable_path = self.executable_path.clone();
+        let log_level = self.log_level;
+        let metrics = self.metrics.clone();
+        let status_tx = self.status_tx.clone();
 
-            let context_path = self.context_path.clone();
-            let executable_path = self.executable_path.clone();
+        let closed = Arc::new(AtomicBool::new(false));
+        self.detached_shutdown = Some(closed.clone());
 
-            
-            tokio::spawn(async move {
-                //for range in ranges {
-                    let range = if to_current {
-                        format!("current/{}", to - from + 1)
-                    } else {
-                        format!("{}/{}", to, to - from + 1)
-                    };
+        tokio::spawn(async move {
+            for range in ranges {
+                if closed.load(Ordering::Relaxed)
+                    || shutdown.as_ref().is_some_and(|shutdown| *shutdown.borrow())
+                {
+                    break;
+                }
 
-                    let process = run_core_cli(
-                        &[
-                            "catchup",
-                            &range,
-                            "--metadata-output-stream fd:1",
-                        ],
-                        &context_path,
-                        &executable_path,
-                    ).unwrap();
-
-                    let stdout = process.stdout.unwrap();
-                    let reader = BufReader::new(stdout);
-                    //let _ = Self::inner_start_from_pipe(reader, transmitter.clone()).await.unwrap();
-                    let mut stateless_ledger_buffer_reader = match BufferedLedgerMetaReader::new(
-                        BufferedLedgerMetaReaderMode::MultiThread,
-                        Box::new(reader),
-                        None,
-                        None,
-                        Some(transmitter.clone()),
-                        None
-                    ) {
-                        Ok(reader) => reader,
-                        Err(error) => return Err(RunnerError::MetaReader(error)),
-                    };
-        
-                    stateless_ledger_buffer_reader
-                        .async_multi_thread_read_ledger_meta_from_pipe()
-                        .await
-                        .unwrap();
-                //};
+                let result = async_catchup_range(
+                    &range,
+                    &context_path,
+                    &executable_path,
+                    log_level,
+                    metrics.clone(),
+                    transmitter.clone(),
+                    bounded_transmitter.clone(),
+                    shutdown.clone(),
+                )
+                .await;
+
+                if let Err(error) = result {
+                    logging::error!("Catchup of {} failed: {}", range, error);
+                    break;
+                }
+            }
 
-                Ok(())
-            });
-            Ok(receiver)
-        }
+            finish_detached(&status_tx, &closed);
+        });
+
+        Ok(receiver)
     }
 
-    pub async fn run_async(&mut self) -> Result<tokio::sync::mpsc::UnboundedReceiver<Box<MetaResult>>, RunnerError> {
-        if self.status != RunnerStatus::Closed {
+    pub async fn run_async(&mut self) -> Result<AsyncMetaReceiver, RunnerError> {
+        if !self.is_closed() {
             return Err(RunnerError::AlreadyRunning);
         }
 
-        self.status = RunnerStatus::RunningOnline;
+        self.set_status(RunnerStatus::RunningOnline);
 
         // Creating/resetting the DB and a quick catchup.
         // TODO: optimize this process by checking what's the
@@ -640,11 +1536,14 @@ impl StellarCoreRunner {
             //self.run_core_cli(&["new-db"])?;
             //self.process.as_mut().unwrap().wait().unwrap();
 
-            let _ = self.run_core_cli(&["catchup", "current/2"]);
-            self.process.as_mut().unwrap().wait().unwrap();
+            self.run_core_cli(&["catchup", "current/2"])?;
+            self.process
+                .as_mut()
+                .ok_or(RunnerError::ProcessNotFound)?
+                .wait()?;
         }
 
-        self.run_core_cli(&["run", "--metadata-output-stream fd:1"])?;
+        self.run_core_cli(&["run", "--metadata-output-stream", "fd:1"])?;
         let stdout = self.process.as_mut().unwrap().stdout.take().unwrap(); // TODO: handle panic;
 
         let reader = BufReader::new(stdout);
@@ -664,9 +1563,11 @@ impl StellarCoreRunner {
                 Some(transmitter),
                 None,
                 None,
+                None,
+                None,
                 None
             ) {
-                Ok(reader) => reader,
+                Ok(reader) => reader.with_metrics(self.metrics.clone()),
                 Err(error) => return Err(RunnerError::MetaReader(error)),
             };
 
@@ -695,9 +1596,11 @@ impl StellarCoreRunner {
                 None,
                 Some(transmitter),
                 None,
+                None,
+                None,
                 None
             ) {
-                Ok(reader) => reader,
+                Ok(reader) => reader.with_metrics(self.metrics.clone()),
                 Err(error) => return Err(RunnerError::MetaReader(error)),
             };
 
@@ -716,26 +1619,28 @@ impl StellarCoreRunner {
     async fn start_and_transmitter_async(
         &mut self,
         reader: BufReader<ChildStdout>,
-    ) -> Result<tokio::sync::mpsc::UnboundedReceiver<Box<MetaResult>>, RunnerError> {
-        let (transmitter, receiver) = tokio::sync::mpsc::unbounded_channel();
+    ) -> Result<AsyncMetaReceiver, RunnerError> {
+        let (transmitter, bounded_transmitter, receiver) =
+            async_meta_channel(self.bounded_buffer_size);
         let _handle = {
-            let mut stateless_ledger_buffer_reader = match BufferedLedgerMetaReader::new(
+            let stateless_ledger_buffer_reader = match BufferedLedgerMetaReader::new(
                 BufferedLedgerMetaReaderMode::MultiThread,
                 Box::new(reader),
                 None,
                 None,
-                Some(transmitter),
+                transmitter,
+                bounded_transmitter,
+                None,
                 None
             ) {
-                Ok(reader) => reader,
+                Ok(reader) => reader.with_metrics(self.metrics.clone()),
                 Err(error) => return Err(RunnerError::MetaReader(error)),
             };
 
             self.ledger_buffer_reader = Some(stateless_ledger_buffer_reader.clone());
 
             tokio::spawn(async move {
-                stateless_ledger_buffer_reader
-                    .async_multi_thread_read_ledger_meta_from_pipe()
+                read_pipe_blocking(stateless_ledger_buffer_reader)
                     .await
                     .unwrap()
             })
@@ -745,21 +1650,149 @@ impl StellarCoreRunner {
     }
 }
 
+// Runs a single catchup and forwards its metadata to the transmitters. The
+// process is owned by a supervisor task that kills it if `shutdown` is set,
+// which closes the pipe and lets the read loop end gracefully.
+#[allow(clippy::too_many_arguments)]
+async fn async_catchup_range(
+    range: &str,
+    context_path: &str,
+    executable_path: &str,
+    log_level: CoreLogLevel,
+    metrics: Arc<IngestionMetrics>,
+    transmitter: Option<UnboundedSender<Box<MetaResult>>>,
+    bounded_transmitter: Option<tokio::sync::mpsc::Sender<Box<MetaResult>>>,
+    shutdown: Option<watch::Receiver<bool>>,
+) -> Result<(), RunnerError> {
+    let mut process = run_core_cli(
+        &["catchup", range, "--metadata-output-stream", "fd:1"],
+        context_path,
+        executable_path,
+        log_level,
+    )?;
+    let stdout = process.stdout.take().ok_or(RunnerError::ProcessNotFound)?;
+
+    let (done_transmitter, done_receiver) = oneshot::channel();
+    tokio::spawn(supervise_process(process, shutdown, done_receiver));
+
+    let stateless_ledger_buffer_reader = BufferedLedgerMetaReader::new(
+        BufferedLedgerMetaReaderMode::MultiThread,
+        Box::new(BufReader::new(stdout)),
+        None,
+        None,
+        transmitter,
+        bounded_transmitter,
+        None,
+        None,
+    )?
+    .with_metrics(metrics);
+
+    let result = read_pipe_blocking(stateless_ledger_buffer_reader).await;
+    let _ = done_transmitter.send(());
+
+    result
+}
+
+// Reads the pipe on the blocking thread pool. Reading `ChildStdout` blocks, so
+// on a runtime worker it would hold up the worker for the whole catchup, and
+// every other task on a `current_thread` runtime.
+async fn read_pipe_blocking(mut reader: BufferedLedgerMetaReader) -> Result<(), RunnerError> {
+    let handle = tokio::runtime::Handle::current();
+
+    tokio::task::spawn_blocking(move || {
+        handle.block_on(reader.async_multi_thread_read_ledger_meta_from_pipe())
+    })
+    .await
+    .map_err(|error| RunnerError::Process(io::Error::other(error)))?
+    .map_err(RunnerError::MetaReader)
+}
+
+// Publishes `Closed` once a detached background job ends on its own, so the
+// runner that started it can be started again. The check runs under the lock
+// of the status channel, so it can't overwrite the status of a runner that
+// was closed and started again in the meantime.
+fn finish_detached(status_tx: &watch::Sender<RunnerStatus>, closed: &AtomicBool) {
+    status_tx.send_if_modified(|status| {
+        let finished = !closed.load(Ordering::Relaxed);
+        if finished {
+            *status = RunnerStatus::Closed;
+        }
+
+        finished
+    });
+}
+
+// Waits for either the pipe to be exhausted or for `shutdown` to be set, in
+// which case the process is killed, then reaps the process.
+async fn supervise_process(
+    mut process: Child,
+    mut shutdown: Option<watch::Receiver<bool>>,
+    mut done: oneshot::Receiver<()>,
+) {
+    let mut finished = false;
+
+    if let Some(shutdown) = shutdown.as_mut() {
+        tokio::select! {
+            _ = &mut done => finished = true,
+            Ok(_) = shutdown.wait_for(|cancelled| *cancelled) => {
+                logging::info!("Catchup cancelled, killing stellar-core");
+                let _ = process.kill();
+            }
+        }
+    }
+
+    if !finished {
+        let _ = done.await;
+    }
+
+    let _ = tokio::task::spawn_blocking(move || process.wait()).await;
+}
+
+// Creates the channel used by the async APIs, bounded if `bound` is set.
+// Exactly one of the two transmitters is returned.
+#[allow(clippy::type_complexity)]
+fn async_meta_channel(
+    bound: Option<usize>,
+) -> (
+    Option<UnboundedSender<Box<MetaResult>>>,
+    Option<tokio::sync::mpsc::Sender<Box<MetaResult>>>,
+    AsyncMetaReceiver,
+) {
+    if let Some(bound) = bound {
+        let (transmitter, receiver) = tokio::sync::mpsc::channel(bound);
+        (
+            None,
+            Some(transmitter),
+            AsyncMetaReceiver::Bounded(receiver),
+        )
+    } else {
+        let (transmitter, receiver) = tokio::sync::mpsc::unbounded_channel();
+        (
+            Some(transmitter),
+            None,
+            AsyncMetaReceiver::Unbounded(receiver),
+        )
+    }
+}
+
 fn run_core_cli(
     args: &[&str],
     context_path: &str,
     executable_path: &str,
+    log_level: CoreLogLevel,
 ) -> Result<Child, RunnerError> {
-    let conf_arg = format!("--conf {}/stellar-core.cfg", context_path);
+    let conf_path = format!("{}/stellar-core.cfg", context_path);
 
     let mut cmd = Command::new(executable_path);
     for arg in args {
         cmd.arg(arg);
     }
     cmd.current_dir(context_path)
-        .arg(conf_arg)
+        .arg("--conf")
+        .arg(conf_path)
         //.arg("--in-memory") // TODO: manage in-memory or DB running on implementor choice.
-        .arg("--ll INFO");
+        .arg("--ll")
+        .arg(log_level.as_str());
 
     let cmd = cmd.stdout(std::process::Stdio::piped()).spawn();
 
@@ -1,3 +1,10 @@
+use std::{
+    path::PathBuf,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::{QuorumSetConfig, ValidatorEntry};
+
 /// Context path object.
 pub struct ContextPath(pub String);
 
@@ -7,8 +14,52 @@ impl Default for ContextPath {
     }
 }
 
+// disambiguates the paths generated by `ContextPath::auto_unique`
+// within the same process.
+static CONTEXT_PATH_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+impl ContextPath {
+    /// Creates a context path that no other `auto_unique` path uses,
+    /// in the form `/tmp/rs_ingestion_temp_<pid>_<counter>`.
+    ///
+    /// Useful when running multiple captive cores at once, which
+    /// would otherwise clobber each other's config and database.
+    pub fn auto_unique() -> Self {
+        let counter = CONTEXT_PATH_COUNTER.fetch_add(1, Ordering::Relaxed);
+
+        Self::with_suffix(&format!("{}_{}", std::process::id(), counter))
+    }
+
+    /// Creates a context path in the form `/tmp/rs_ingestion_temp_<suffix>`.
+    pub fn with_suffix(suffix: &str) -> Self {
+        Self(format!("{}_{}", Self::default().0, suffix))
+    }
+}
+
+// serialized as the bare path string rather than as a newtype.
+#[cfg(feature = "serde")]
+impl serde::Serialize for ContextPath {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ContextPath {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self)
+    }
+}
+
+#[cfg(feature = "serde")]
+fn default_config_override() -> bool {
+    true
+}
+
 /// Enum describing the network to run stellar-core on.
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum SupportedNetwork {
     /// Ingest on futurenet.
     Futurenet,
@@ -20,7 +71,85 @@ pub enum SupportedNetwork {
     Testnet
 }
 
+impl SupportedNetwork {
+    /// Gets the network passphrase, e.g. to hash or sign transactions.
+    pub fn passphrase(&self) -> &'static str {
+        match self {
+            SupportedNetwork::Futurenet => "Test SDF Future Network ; October 2022",
+            SupportedNetwork::Pubnet => "Public Global Stellar Network ; September 2015",
+            SupportedNetwork::Testnet => "Test SDF Network ; September 2015",
+        }
+    }
+
+    /// Gets an owned copy of the network passphrase.
+    pub fn passphrase_owned(&self) -> String {
+        self.passphrase().to_string()
+    }
+
+    /// Gets the network with the given passphrase.
+    ///
+    /// # Returns
+    ///
+    /// Returns the matching network, or `None` if the passphrase doesn't
+    /// belong to any of the supported networks.
+    pub fn from_passphrase(passphrase: &str) -> Option<Self> {
+        [
+            SupportedNetwork::Futurenet,
+            SupportedNetwork::Pubnet,
+            SupportedNetwork::Testnet,
+        ]
+        .into_iter()
+        .find(|network| network.passphrase() == passphrase)
+    }
+}
+
+/// Log level of the stellar-core subprocess, passed as its `--ll` option.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum CoreLogLevel {
+    Fatal,
+    Error,
+    Warning,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl CoreLogLevel {
+    /// Gets the name of the level as expected by stellar-core, e.g. `INFO`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Fatal => "FATAL",
+            Self::Error => "ERROR",
+            Self::Warning => "WARNING",
+            Self::Info => "INFO",
+            Self::Debug => "DEBUG",
+            Self::Trace => "TRACE",
+        }
+    }
+}
+
+/// Errors encountered while building an `IngestionConfig`.
+#[derive(thiserror::Error, Debug)]
+pub enum ConfigError {
+    /// A required environment variable is not set.
+    #[error("Missing environment variable {0}")]
+    MissingEnvVar(String),
+
+    /// An environment variable is set to a value that can't be parsed.
+    #[error("Invalid value {1:?} for environment variable {0}")]
+    InvalidEnvVar(String, String),
+}
+
 /// Configuration settings
+///
+/// With the `serde` feature enabled the config can be (de)serialized,
+/// e.g. to load it from a TOML file. `context_path`, `validators`,
+/// `keep_data_on_drop` and `history_archive_headers` fall back to their
+/// defaults when missing, and `config_override` to `true`.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct IngestionConfig {
     /// Path to the stellar-core executable.
     pub executable_path: String,
@@ -28,6 +157,7 @@ pub struct IngestionConfig {
     /// Path to the context directory.
     /// The context directory is where temporary buckets
     /// database, and toml configuration are stored.
+    #[cfg_attr(feature = "serde", serde(default))]
     pub context_path: ContextPath,
 
     /// Network to run stellar-core on.
@@ -40,10 +170,25 @@ pub struct IngestionConfig {
     /// adapt to how they handle the receiver.
     pub bounded_buffer_size: Option<usize>,
 
-    /// Option to split multi-thread mode catchups
-    /// to produce staggered and help with write
-    /// amount in databases for large catchups.
-    /// 
+    /// Option to split multi-thread mode catchups into
+    /// consecutive catchups of about `stagger_step_size`
+    /// ledgers each, to produce staggered data and help
+    /// with write amount in databases for large catchups.
+    ///
+    /// The range is split into `(to - from) / stagger_step_size`
+    /// steps of equal size, so the steps can be slightly larger
+    /// than `stagger_step_size`. If the range doesn't fit at
+    /// least two steps it is caught up at once, and a warning
+    /// is logged since the setting has no effect.
+    ///
+    /// For example with `Some(10_000)`, a catchup of ledgers
+    /// `1..=100_000` runs ten catchups of 10_000 ledgers, while
+    /// a catchup of 15_000 ledgers isn't staggered. Values in
+    /// the order of a few thousands to tens of thousands of
+    /// ledgers are typical, smaller steps add the overhead of
+    /// starting stellar-core for every step. `Some(0)` is
+    /// treated like `None`.
+    ///
     /// This option will help to stagger large catchup
     /// data, enabling for checkpoints.
     /// 
@@ -52,5 +197,189 @@ pub struct IngestionConfig {
     /// make sure you understand what it does
     /// and try out bounded buffers or
     /// handling large catchup data yourself first.
-    pub staggered: Option<u32>,
+    ///
+    /// Previously named `staggered`, which is still accepted
+    /// when deserializing.
+    #[cfg_attr(feature = "serde", serde(alias = "staggered"))]
+    pub stagger_step_size: Option<u32>,
+
+    /// Whether to (re)generate the predefined `stellar-core.cfg`
+    /// in the context directory when creating the captive core.
+    ///
+    /// Set to `true` for the previous behaviour of always overwriting it,
+    /// or to `false` to keep a hand-tuned configuration (e.g. with
+    /// additional peers or quorum sets), which must then already exist
+    /// in the context directory.
+    ///
+    /// Defaults to `true` when missing from a deserialized config,
+    /// for backward compatibility.
+    #[cfg_attr(feature = "serde", serde(default = "default_config_override"))]
+    pub config_override: bool,
+
+    /// Validators to write in the generated `stellar-core.cfg`.
+    /// When empty, the predefined single-validator config of
+    /// the network is used.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub validators: Vec<ValidatorEntry>,
+
+    /// Optional explicit quorum set over `validators`. When set,
+    /// `UNSAFE_QUORUM` is not written to the generated config.
+    /// Every name in the quorum set must be one of `validators`.
+    pub quorum_set: Option<QuorumSetConfig>,
+
+    /// Optional checkpointing of multi-thread catchups as
+    /// `(every, path)`: after every `every` ledgers their
+    /// XDR frames are appended to the file at `path`, so that
+    /// an interrupted catchup can be resumed with
+    /// `CaptiveCore::resume_from_checkpoint`.
+    pub checkpoint_every: Option<(u32, PathBuf)>,
+
+    /// Whether to keep the buckets and the database in
+    /// the context directory when the captive core is
+    /// dropped, e.g. for debugging. By default (`false`)
+    /// they are removed.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub keep_data_on_drop: bool,
+
+    /// Optional minimum version of the stellar-core executable.
+    /// When set, `CaptiveCore::new` fails if `executable_path`
+    /// reports an older version.
+    pub min_stellar_core_version: Option<semver::Version>,
+
+    /// Optional port of stellar-core's HTTP API, which is
+    /// disabled when `None`. Required by
+    /// `StellarCoreRunner::get_latest_closed_ledger_via_http`.
+    pub http_port: Option<u16>,
+
+    /// HTTP headers sent when fetching files from the history
+    /// archives, e.g. `("Authorization", "Bearer <token>")` for
+    /// private archives. When not empty, the `curl -sf` history
+    /// commands of the generated `stellar-core.cfg` go through a
+    /// wrapper script written to the context directory.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub history_archive_headers: Vec<(String, String)>,
+
+    /// Optional `CATCHUP_COMPLETE` of the generated `stellar-core.cfg`,
+    /// whether stellar-core replays the whole history when it starts
+    /// running online. Takes precedence over `catchup_recent`.
+    ///
+    /// Like `catchup_recent`, this only affects the catchup done by
+    /// `stellar-core run`: offline catchups, including `stagger_step_size`
+    /// ones, always replay the explicitly requested range.
+    pub catchup_complete: Option<bool>,
+
+    /// Optional `CATCHUP_RECENT` of the generated `stellar-core.cfg`,
+    /// the amount of recent ledgers replayed when stellar-core starts
+    /// running online.
+    pub catchup_recent: Option<u32>,
+
+    /// Optional `MAX_SLOTS_TO_REMEMBER` of the generated `stellar-core.cfg`,
+    /// the amount of SCP slots stellar-core keeps in memory. Lower values
+    /// reduce memory usage, e.g. for long in-memory catchups.
+    pub max_slots_to_remember: Option<u32>,
+
+    /// Whether to check that the history archives are reachable before
+    /// starting an offline catchup, so that it fails right away rather
+    /// than hours in. Disabled by default.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub check_archive_before_catchup: bool,
+
+    /// Log level of stellar-core, `CoreLogLevel::Info` by default. E.g.
+    /// `Debug` or `Trace` help debugging, while `Warning` reduces noise.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub log_level: CoreLogLevel,
+}
+
+impl Default for IngestionConfig {
+    /// Ingests futurenet with the stellar-core executable at
+    /// `/usr/local/bin/stellar-core` and the default context path.
+    /// The optional settings are unset, the predefined config is
+    /// always generated and data is removed on drop.
+    ///
+    /// Lets callers set only the fields they need, e.g.
+    /// `IngestionConfig { network: SupportedNetwork::Pubnet, ..Default::default() }`.
+    fn default() -> Self {
+        Self {
+            executable_path: "/usr/local/bin/stellar-core".to_string(),
+            context_path: ContextPath::default(),
+            network: SupportedNetwork::Futurenet,
+            bounded_buffer_size: None,
+            stagger_step_size: None,
+            config_override: true,
+            validators: Vec::new(),
+            quorum_set: None,
+            checkpoint_every: None,
+            keep_data_on_drop: false,
+            min_stellar_core_version: None,
+            http_port: None,
+            history_archive_headers: Vec::new(),
+            catchup_complete: None,
+            catchup_recent: None,
+            max_slots_to_remember: None,
+            check_archive_before_catchup: false,
+            log_level: CoreLogLevel::Info,
+        }
+    }
+}
+
+impl IngestionConfig {
+    /// Returns `stagger_step_size`.
+    #[deprecated(note = "use `IngestionConfig::stagger_step_size` instead")]
+    pub fn staggered(&self) -> Option<u32> {
+        self.stagger_step_size
+    }
+
+    /// Reads the configuration from environment variables:
+    ///
+    /// * `STELLAR_CORE_PATH` - `executable_path`, required.
+    /// * `INGEST_NETWORK` - `network`, one of `futurenet`, `pubnet` or `testnet`, required.
+    /// * `INGEST_CONTEXT_PATH` - `context_path`, defaults to the default context path.
+    /// * `INGEST_BOUNDED_BUFFER` - `bounded_buffer_size`, unbounded if unset.
+    /// * `INGEST_STAGGER` - `stagger_step_size`, not staggered if unset.
+    ///
+    /// The other settings take their default values: the predefined config
+    /// is always generated for the network and data is removed on drop.
+    ///
+    /// # Returns
+    ///
+    /// Returns the config, or a `ConfigError` if a required variable is missing
+    /// or a variable can't be parsed.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        let network = match required_env_var("INGEST_NETWORK")?.as_str() {
+            "futurenet" => SupportedNetwork::Futurenet,
+            "pubnet" => SupportedNetwork::Pubnet,
+            "testnet" => SupportedNetwork::Testnet,
+            other => {
+                return Err(ConfigError::InvalidEnvVar(
+                    "INGEST_NETWORK".to_string(),
+                    other.to_string(),
+                ))
+            }
+        };
+
+        Ok(Self {
+            executable_path: required_env_var("STELLAR_CORE_PATH")?,
+            context_path: std::env::var("INGEST_CONTEXT_PATH")
+                .map(ContextPath)
+                .unwrap_or_default(),
+            network,
+            bounded_buffer_size: parsed_env_var("INGEST_BOUNDED_BUFFER")?,
+            stagger_step_size: parsed_env_var("INGEST_STAGGER")?,
+            ..Default::default()
+        })
+    }
+}
+
+fn required_env_var(name: &str) -> Result<String, ConfigError> {
+    std::env::var(name).map_err(|_| ConfigError::MissingEnvVar(name.to_string()))
+}
+
+fn parsed_env_var<T: std::str::FromStr>(name: &str) -> Result<Option<T>, ConfigError> {
+    match std::env::var(name) {
+        Ok(value) => value
+            .parse()
+            .map(Some)
+            .map_err(|_| ConfigError::InvalidEnvVar(name.to_string(), value)),
+        Err(_) => Ok(None),
+    }
 }
@@ -0,0 +1,177 @@
+use std::sync::Arc;
+
+use stellar_xdr::next::LedgerCloseMeta;
+
+use crate::MetaResult;
+
+/// Ledgers prepared by [`CaptiveCore::prepare_ledgers_single_thread`](crate::CaptiveCore::prepare_ledgers_single_thread),
+/// ordered by sequence.
+///
+/// A batch is independent from the captive core that prepared it, so
+/// batches of different ranges can be kept at the same time. Like the
+/// prepared ledgers, a batch may include ledgers outside of the requested
+/// range because of checkpoint alignment.
+///
+/// The ledgers are shared with the captive core rather than copied, until
+/// the captive core prepares other ledgers or is dropped.
+#[derive(Clone, Debug)]
+pub struct LedgerBatch {
+    inner: Arc<Vec<MetaResult>>,
+
+    // sequence and index in `inner` of every ledger, sorted by sequence.
+    // Results holding an error are left out.
+    sequences: Vec<(u32, usize)>,
+}
+
+impl LedgerBatch {
+    pub(crate) fn new(results: Arc<Vec<MetaResult>>) -> Self {
+        let mut sequences: Vec<(u32, usize)> = results
+            .iter()
+            .enumerate()
+            .filter_map(|(index, result)| result.ledger_sequence().map(|seq| (seq, index)))
+            .collect();
+        sequences.sort_unstable();
+
+        Self {
+            inner: results,
+            sequences,
+        }
+    }
+
+    /// Gets the ledger with the given sequence.
+    ///
+    /// # Arguments
+    ///
+    /// * `seq` - The sequence of the ledger.
+    ///
+    /// # Returns
+    ///
+    /// Returns the ledger, or `None` if it is not in the batch.
+    pub fn get(&self, seq: u32) -> Option<&LedgerCloseMeta> {
+        let position = self
+            .sequences
+            .binary_search_by_key(&seq, |(seq, _)| *seq)
+            .ok()?;
+
+        self.inner[self.sequences[position].1]
+            .ledger_close_meta_ref()
+            .ok()
+    }
+
+    /// Gets the amount of ledgers in the batch, not counting the results
+    /// that hold an error.
+    pub fn count(&self) -> usize {
+        self.sequences.len()
+    }
+
+    /// Gets the sequences of the ledgers in the batch, in ascending order.
+    pub fn sequences(&self) -> Vec<u32> {
+        self.sequences.iter().map(|(seq, _)| *seq).collect()
+    }
+
+    /// Iterates over the ledgers in ascending sequence order without
+    /// cloning them, skipping the results that hold an error.
+    pub fn iter(&self) -> impl Iterator<Item = &LedgerCloseMeta> + '_ {
+        self.sequences
+            .iter()
+            .filter_map(|(_, index)| self.inner[*index].ledger_close_meta_ref().ok())
+    }
+}
+
+impl IntoIterator for LedgerBatch {
+    type Item = LedgerCloseMeta;
+    type IntoIter = std::vec::IntoIter<LedgerCloseMeta>;
+
+    /// Iterates over the ledgers in ascending sequence order, skipping the
+    /// results that hold an error.
+    ///
+    /// The ledgers are moved out of the batch if nothing else shares them,
+    /// and cloned otherwise. Use [`LedgerBatch::iter`] to borrow them instead.
+    fn into_iter(self) -> Self::IntoIter {
+        let ledgers: Vec<LedgerCloseMeta> = match Arc::try_unwrap(self.inner) {
+            Ok(results) => {
+                let mut results: Vec<Option<MetaResult>> = results.into_iter().map(Some).collect();
+                self.sequences
+                    .iter()
+                    .filter_map(|(_, index)| results[*index].take())
+                    .filter_map(|result| result.into_ledger_close_meta().ok())
+                    .collect()
+            }
+            Err(shared) => self
+                .sequences
+                .iter()
+                .filter_map(|(_, index)| shared[*index].ledger_close_meta_ref().ok().cloned())
+                .collect(),
+        };
+
+        ledgers.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stellar_xdr::next::LedgerCloseMetaV0;
+
+    use super::*;
+    use crate::{BufReaderError, HeaderReader, LedgerCloseMetaWrapper};
+
+    fn meta_result(seq: u32) -> MetaResult {
+        let mut v0 = LedgerCloseMetaV0::default();
+        v0.ledger_header.header.ledger_seq = seq;
+
+        MetaResult {
+            ledger_close_meta: Some(LedgerCloseMetaWrapper {
+                ledger_close_meta: LedgerCloseMeta::V0(v0),
+            }),
+            err: None,
+        }
+    }
+
+    // ledgers 10..=12 out of order, with a result holding an error.
+    fn batch() -> LedgerBatch {
+        LedgerBatch::new(Arc::new(vec![
+            meta_result(12),
+            MetaResult {
+                ledger_close_meta: None,
+                err: Some(BufReaderError::ReadXdrNext),
+            },
+            meta_result(10),
+            meta_result(11),
+        ]))
+    }
+
+    fn sequences(ledgers: impl Iterator<Item = LedgerCloseMeta>) -> Vec<u32> {
+        ledgers
+            .map(|ledger| HeaderReader::sequence(&ledger))
+            .collect()
+    }
+
+    #[test]
+    fn orders_ledgers_and_skips_errors() {
+        let batch = batch();
+
+        assert_eq!(batch.count(), 3);
+        assert_eq!(batch.sequences(), vec![10, 11, 12]);
+        assert_eq!(sequences(batch.iter().cloned()), vec![10, 11, 12]);
+    }
+
+    #[test]
+    fn gets_ledgers_by_sequence() {
+        let batch = batch();
+
+        assert_eq!(batch.get(11).map(HeaderReader::sequence), Some(11));
+        assert!(batch.get(9).is_none());
+        assert!(batch.get(13).is_none());
+    }
+
+    #[test]
+    fn into_iter_moves_or_clones_ledgers() {
+        let unique = batch();
+        assert_eq!(sequences(unique.into_iter()), vec![10, 11, 12]);
+
+        let shared = batch();
+        let kept = shared.clone();
+        assert_eq!(sequences(shared.into_iter()), vec![10, 11, 12]);
+        assert_eq!(kept.sequences(), vec![10, 11, 12]);
+    }
+}
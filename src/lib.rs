@@ -27,18 +27,44 @@
 //!
 //! > Note: Currently, online mode only supports multi-threaded execution.
 //!
+//! ## Logging
+//! Diagnostics are emitted through the [`log`](https://docs.rs/log) facade. Enabling the `tracing`
+//! feature emits them through [`tracing`](https://docs.rs/tracing) instead, together with a `TRACE`
+//! span for every ledger read from stellar-core.
+//!
 //! To learn more about the crate and check out a couple of examples see the [README](https://github.com/xycloo/rs-ingest/blob/main/README.md)
 //!
 
+mod logging;
+
+mod async_captive_core;
 mod buffered_ledger_meta_reader;
 mod captive_core;
+mod consumer;
 mod core_runner;
 mod ingestion_config;
+mod ledger_batch;
+mod metrics;
+mod pipeline;
 mod toml;
 mod reader;
+mod stream;
+
+#[cfg(feature = "tracing")]
+pub mod bin_helpers;
+
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
+pub use async_captive_core::*;
 pub use buffered_ledger_meta_reader::*;
 pub use captive_core::*;
+pub use consumer::*;
 pub use core_runner::*;
 pub use ingestion_config::*;
+pub use ledger_batch::*;
+pub use metrics::*;
+pub use pipeline::*;
 pub use reader::*;
+pub use stream::*;
+pub use toml::{QuorumSetConfig, ValidatorEntry};
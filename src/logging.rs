@@ -0,0 +1,48 @@
+//! Internal logging macros.
+//!
+//! Diagnostics go through `tracing` when the `tracing` feature is enabled
+//! and through `log` otherwise, so that library users decide where the
+//! output ends up instead of it being printed to stdout.
+//!
+//! `warning` is named that way because `warn` clashes with the built-in attribute.
+
+macro_rules! trace {
+    ($($arg:tt)+) => {{
+        #[cfg(feature = "tracing")]
+        tracing::trace!($($arg)+);
+        #[cfg(not(feature = "tracing"))]
+        log::trace!($($arg)+);
+    }};
+}
+
+macro_rules! info {
+    ($($arg:tt)+) => {{
+        #[cfg(feature = "tracing")]
+        tracing::info!($($arg)+);
+        #[cfg(not(feature = "tracing"))]
+        log::info!($($arg)+);
+    }};
+}
+
+macro_rules! warning {
+    ($($arg:tt)+) => {{
+        #[cfg(feature = "tracing")]
+        tracing::warn!($($arg)+);
+        #[cfg(not(feature = "tracing"))]
+        log::warn!($($arg)+);
+    }};
+}
+
+macro_rules! error {
+    ($($arg:tt)+) => {{
+        #[cfg(feature = "tracing")]
+        tracing::error!($($arg)+);
+        #[cfg(not(feature = "tracing"))]
+        log::error!($($arg)+);
+    }};
+}
+
+pub(crate) use error;
+pub(crate) use info;
+pub(crate) use trace;
+pub(crate) use warning;
@@ -0,0 +1,96 @@
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::time::Instant;
+
+use crate::{LedgerCloseMetaReader, MetaResult};
+
+/// Counters for monitoring ingestion throughput and lag.
+///
+/// Metrics are shared between the runner and every reader it spawns, so they
+/// can be read from any thread while ledgers are being processed.
+#[derive(Debug)]
+pub struct IngestionMetrics {
+    /// Amount of ledger close metas successfully decoded.
+    pub ledgers_processed: AtomicU64,
+
+    /// Amount of bytes read from the stellar-core meta pipe.
+    pub bytes_read: AtomicU64,
+
+    /// Sequence of the last decoded ledger, `0` if none was decoded yet.
+    pub last_processed_sequence: AtomicU32,
+
+    /// Amount of frames that failed to decode or time out.
+    pub processing_errors: AtomicU64,
+
+    /// Time at which the metrics were created.
+    pub start_time: Instant,
+}
+
+impl Default for IngestionMetrics {
+    fn default() -> Self {
+        Self {
+            ledgers_processed: AtomicU64::new(0),
+            bytes_read: AtomicU64::new(0),
+            last_processed_sequence: AtomicU32::new(0),
+            processing_errors: AtomicU64::new(0),
+            start_time: Instant::now(),
+        }
+    }
+}
+
+impl IngestionMetrics {
+    /// Creates a new set of zeroed metrics.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets how many ledgers the ingestion is behind the network.
+    ///
+    /// # Arguments
+    ///
+    /// * `current_network_ledger` - The latest ledger closed by the network.
+    ///
+    /// # Returns
+    ///
+    /// Returns the amount of ledgers between the last processed ledger and the
+    /// network, `0` if the ingestion is up to date.
+    pub fn lag_ledgers(&self, current_network_ledger: u32) -> u32 {
+        current_network_ledger.saturating_sub(self.last_processed_sequence.load(Ordering::Relaxed))
+    }
+
+    /// Gets the average amount of ledgers processed per second since `start_time`.
+    pub fn ledgers_per_second(&self) -> f64 {
+        let elapsed = self.start_time.elapsed().as_secs_f64();
+        if elapsed == 0.0 {
+            return 0.0;
+        }
+
+        self.ledgers_processed.load(Ordering::Relaxed) as f64 / elapsed
+    }
+
+    /// Resets all the counters to zero.
+    ///
+    /// Note that `start_time` is left untouched.
+    pub fn reset(&self) {
+        self.ledgers_processed.store(0, Ordering::Relaxed);
+        self.bytes_read.store(0, Ordering::Relaxed);
+        self.last_processed_sequence.store(0, Ordering::Relaxed);
+        self.processing_errors.store(0, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_bytes(&self, bytes: usize) {
+        self.bytes_read.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_result(&self, result: &MetaResult) {
+        match LedgerCloseMetaReader::ledger_sequence(result) {
+            Ok(sequence) => {
+                self.ledgers_processed.fetch_add(1, Ordering::Relaxed);
+                self.last_processed_sequence
+                    .store(sequence, Ordering::Relaxed);
+            }
+            Err(_) => {
+                self.processing_errors.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
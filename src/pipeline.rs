@@ -0,0 +1,144 @@
+use std::sync::mpsc::Receiver;
+
+use stellar_xdr::next::LedgerCloseMeta;
+
+use crate::{logging, AsyncMetaReceiver, BufReaderError, MetaResult};
+
+/// A step of an [`IngestPipeline`].
+pub trait LedgerProcessor: Send {
+    /// Processes a single ledger.
+    ///
+    /// # Arguments
+    ///
+    /// * `ledger` - The ledger close meta to process.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if the ledger was processed, or an error to stop
+    /// processing this ledger.
+    fn process(
+        &mut self,
+        ledger: &LedgerCloseMeta,
+    ) -> Result<(), Box<dyn std::error::Error + Send>>;
+}
+
+impl<F> LedgerProcessor for F
+where
+    F: FnMut(&LedgerCloseMeta) -> Result<(), Box<dyn std::error::Error + Send>> + Send,
+{
+    fn process(
+        &mut self,
+        ledger: &LedgerCloseMeta,
+    ) -> Result<(), Box<dyn std::error::Error + Send>> {
+        self(ledger)
+    }
+}
+
+/// Represents the errors that can stop an [`IngestPipeline`].
+#[derive(thiserror::Error, Debug)]
+pub enum PipelineError {
+    /// A processor failed and the pipeline aborts on errors.
+    #[error("Processor {index} failed: {error}")]
+    Processor {
+        /// Position of the failing processor in the pipeline.
+        index: usize,
+
+        /// Error returned by the processor.
+        error: Box<dyn std::error::Error + Send>,
+    },
+
+    /// Error encountered while reading ledger metadata.
+    #[error("Error in reading ledger metadata: {0}")]
+    MetaReader(#[from] BufReaderError),
+}
+
+/// Runs a sequence of [`LedgerProcessor`]s on every ledger of a runner's receiver.
+///
+/// For each ledger the processors are called in the order they were added. A
+/// processor error skips the remaining processors for that ledger, and also
+/// stops the pipeline if `abort_on_error` is set.
+#[derive(Default)]
+pub struct IngestPipeline {
+    processors: Vec<Box<dyn LedgerProcessor>>,
+
+    abort_on_error: bool,
+}
+
+impl IngestPipeline {
+    /// Creates a new empty pipeline which doesn't abort on errors.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a processor at the end of the pipeline.
+    pub fn add_processor(mut self, processor: impl LedgerProcessor + 'static) -> Self {
+        self.processors.push(Box::new(processor));
+        self
+    }
+
+    /// Sets whether a processor error stops the whole pipeline.
+    pub fn abort_on_error(mut self, abort_on_error: bool) -> Self {
+        self.abort_on_error = abort_on_error;
+        self
+    }
+
+    /// Runs the pipeline until the receiver is closed or the catchup job finishes.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` once all ledgers were processed, or a `PipelineError` if
+    /// reading fails or a processor fails with `abort_on_error` set.
+    pub fn execute(&mut self, receiver: Receiver<Box<MetaResult>>) -> Result<(), PipelineError> {
+        for result in receiver.iter() {
+            if !self.process_result(*result)? {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Async version of [`IngestPipeline::execute`].
+    pub async fn execute_async(
+        &mut self,
+        mut receiver: AsyncMetaReceiver,
+    ) -> Result<(), PipelineError> {
+        while let Some(result) = receiver.recv().await {
+            if !self.process_result(*result)? {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Returns whether the pipeline should keep receiving ledgers.
+    fn process_result(&mut self, result: MetaResult) -> Result<bool, PipelineError> {
+        // the pipe is closed once the catchup job finishes.
+        if result.is_catchup_end_sentinel() {
+            return Ok(false);
+        }
+
+        let ledger = match (result.ledger_close_meta, result.err) {
+            (Some(wrapper), _) => wrapper.ledger_close_meta,
+            (None, error) => {
+                return Err(PipelineError::MetaReader(
+                    error.unwrap_or(BufReaderError::ReadXdrNext),
+                ))
+            }
+        };
+
+        for (index, processor) in self.processors.iter_mut().enumerate() {
+            if let Err(error) = processor.process(&ledger) {
+                if self.abort_on_error {
+                    return Err(PipelineError::Processor { index, error });
+                }
+
+                logging::error!("Processor {} failed, skipping ledger: {}", index, error);
+                break;
+            }
+        }
+
+        Ok(true)
+    }
+}
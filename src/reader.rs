@@ -1,19 +1,43 @@
-use stellar_xdr::next::{LedgerCloseMeta, TransactionEnvelope, GeneralizedTransactionSet, TransactionPhase, TxSetComponent, TransactionResultMeta, SorobanTransactionMeta, TransactionMeta, ContractEvent};
+use std::collections::{HashMap, HashSet};
 
-use crate::{MetaResult, BufReaderError};
+use stellar_xdr::next::{LedgerCloseMeta, TransactionEnvelope, GeneralizedTransactionSet, TransactionPhase, TxSetComponent, TransactionResultMeta, SorobanTransactionMeta, TransactionMeta, ContractEvent, Operation, OperationBody, OperationType, FeeBumpTransactionInnerTx, AccountId, MuxedAccount, PublicKey, ScAddress, SorobanCredentials, Hash, HostFunction, LedgerEntry, LedgerEntryChange, LedgerEntryData, OperationMeta, TransactionResultResult, ContractDataEntry, ContractCodeEntry, SorobanAuthorizationEntry, TransactionResultCode, LedgerFootprint, TransactionExt, Limits, Preconditions, Transaction, TransactionSignaturePayload, TransactionSignaturePayloadTaggedTransaction, WriteXdr, TimeBounds, Memo, ScVal, ContractEventBody, AccountEntry, TrustLineEntry, TrustLineAsset, OfferEntry, DataEntry, LiquidityPoolEntry, ClaimableBalanceEntry, LedgerHeader, InflationResult, OperationResult, OperationResultTr, InnerTransactionResultResult, StellarValue, RevokeSponsorshipOp, LedgerKey, ClaimableBalanceId, CreateClaimableBalanceResult, BeginSponsoringFutureReservesOp, LedgerEntryExt, PoolId, LiquidityPoolDepositOp, LiquidityPoolWithdrawOp};
+
+use sha2::{Digest, Sha256};
+
+use crate::{MetaResult, BufReaderError, SupportedNetwork};
 
 #[derive(thiserror::Error, Debug, Clone)]
 pub enum ReaderError {
     #[error("Error while reading meta result {0}")]
-    MetaResult(BufReaderError)
+    MetaResult(BufReaderError),
+
+    #[error("Failed to encode XDR {0}")]
+    Encode(String),
+
+    #[error("No envelope found for transaction result {0:?}")]
+    MissingEnvelope([u8; 32]),
+
+    #[error("No transactions found in the given ledgers")]
+    NoTransactions,
+
+    #[error("Invalid percentile {0}, expected a value between 0 and 100")]
+    InvalidPercentile(f64),
+
+}
 
+/// Kind of change applied to a ledger entry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LedgerEntryChangeType {
+    Created,
+    Updated,
+    Removed,
 }
 
 pub struct LedgerCloseMetaReader;
 
 impl LedgerCloseMetaReader {
 
-    pub fn ledegr_sequence(result: &MetaResult) -> Result<u32, ReaderError> {
+    pub fn ledger_sequence(result: &MetaResult) -> Result<u32, ReaderError> {
         let meta = MetaResultReader::read_meta(result)?;
 
         match meta {
@@ -27,6 +51,11 @@ impl LedgerCloseMetaReader {
         }
     }
 
+    #[deprecated(note = "use `LedgerCloseMetaReader::ledger_sequence` instead")]
+    pub fn ledegr_sequence(result: &MetaResult) -> Result<u32, ReaderError> {
+        Self::ledger_sequence(result)
+    }
+
     pub fn ledger_hash(result: &MetaResult) -> Result<[u8; 32], ReaderError> {
         let meta = MetaResultReader::read_meta(result)?;
 
@@ -55,6 +84,84 @@ impl LedgerCloseMetaReader {
         }
     }
 
+    /// Returns the SCP value (`StellarValue`) that was externalized to close
+    /// the ledger, holding the transaction set hash, the close time and the
+    /// upgrades applied to the ledger.
+    pub fn scp_value(result: &MetaResult) -> Result<StellarValue, ReaderError> {
+        let meta = MetaResultReader::read_meta(result)?;
+
+        match meta {
+            LedgerCloseMeta::V0(v0) => {
+                Ok(v0.ledger_header.header.scp_value.clone())
+            }
+            LedgerCloseMeta::V1(v1) => {
+                Ok(v1.ledger_header.header.scp_value.clone())
+            }
+        }
+    }
+
+    /// Returns the hash of the transaction set committed in the ledger's SCP
+    /// value.
+    pub fn tx_set_hash(result: &MetaResult) -> Result<[u8; 32], ReaderError> {
+        Ok(Self::scp_value(result)?.tx_set_hash.0)
+    }
+
+    /// Computes the hash of the transaction set in the ledger's meta the way
+    /// stellar-core does: generalized transaction sets are hashed as a whole,
+    /// while legacy ones hash the previous ledger hash followed by each
+    /// transaction envelope.
+    pub fn compute_tx_set_hash(result: &MetaResult) -> Result<[u8; 32], ReaderError> {
+        let meta = MetaResultReader::read_meta(result)?;
+        let mut hasher = Sha256::new();
+
+        match meta {
+            LedgerCloseMeta::V0(v0) => {
+                hasher.update(v0.tx_set.previous_ledger_hash.0);
+                for envelope in v0.tx_set.txs.iter() {
+                    let encoded = envelope
+                        .to_xdr(Limits::none())
+                        .map_err(|error| ReaderError::Encode(error.to_string()))?;
+                    hasher.update(encoded);
+                }
+            }
+            LedgerCloseMeta::V1(v1) => {
+                let encoded = v1
+                    .tx_set
+                    .to_xdr(Limits::none())
+                    .map_err(|error| ReaderError::Encode(error.to_string()))?;
+                hasher.update(encoded);
+            }
+        }
+
+        Ok(hasher.finalize().into())
+    }
+
+    /// Returns whether the transaction set in the ledger's meta matches the
+    /// hash committed in the ledger's SCP value.
+    pub fn verify_tx_set_hash(result: &MetaResult) -> Result<bool, ReaderError> {
+        Ok(Self::tx_set_hash(result)? == Self::compute_tx_set_hash(result)?)
+    }
+
+    /// Returns the hash of the XDR encoded SCP value (`StellarValue`) that was
+    /// externalized to close the ledger.
+    pub fn ledger_close_value_hash(result: &MetaResult) -> Result<[u8; 32], ReaderError> {
+        let meta = MetaResultReader::read_meta(result)?;
+
+        let scp_value = match meta {
+            LedgerCloseMeta::V0(v0) => {
+                &v0.ledger_header.header.scp_value
+            }
+            LedgerCloseMeta::V1(v1) => {
+                &v1.ledger_header.header.scp_value
+            }
+        };
+        let encoded = scp_value
+            .to_xdr(Limits::none())
+            .map_err(|error| ReaderError::Encode(error.to_string()))?;
+
+        Ok(Sha256::digest(encoded).into())
+    }
+
     pub fn protocol_version(result: &MetaResult) -> Result<u32, ReaderError> {
         let meta = MetaResultReader::read_meta(result)?;
 
@@ -69,6 +176,26 @@ impl LedgerCloseMetaReader {
         }
     }
 
+    /// Returns whether the protocol version changed between two adjacent
+    /// ledgers, i.e. whether a protocol upgrade was applied in `current`.
+    pub fn is_protocol_upgrade(prev: &MetaResult, current: &MetaResult) -> Result<bool, ReaderError> {
+        Ok(Self::protocol_version_change(prev, current)?.is_some())
+    }
+
+    /// Returns the protocol versions of two adjacent ledgers as
+    /// `Some((old_version, new_version))` if they differ, or `None` if they
+    /// match.
+    pub fn protocol_version_change(prev: &MetaResult, current: &MetaResult) -> Result<Option<(u32, u32)>, ReaderError> {
+        let old_version = Self::protocol_version(prev)?;
+        let new_version = Self::protocol_version(current)?;
+
+        if old_version != new_version {
+            Ok(Some((old_version, new_version)))
+        } else {
+            Ok(None)
+        }
+    }
+
     pub fn bucket_list_hash(result: &MetaResult) -> Result<[u8; 32], ReaderError> {
         let meta = MetaResultReader::read_meta(result)?;
 
@@ -83,60 +210,144 @@ impl LedgerCloseMetaReader {
         }
     }
 
-    pub fn count_transactions(result: &MetaResult) -> Result<usize, ReaderError> {
+    pub fn total_coins(result: &MetaResult) -> Result<i64, ReaderError> {
         let meta = MetaResultReader::read_meta(result)?;
 
         match meta {
             LedgerCloseMeta::V0(v0) => {
-                Ok(v0.tx_processing.len())
+                Ok(v0.ledger_header.header.total_coins)
             }
             LedgerCloseMeta::V1(v1) => {
-                Ok(v1.tx_processing.len())
+                Ok(v1.ledger_header.header.total_coins)
             }
-          
         }
     }
 
-    pub fn transaction_envelopes(result: &MetaResult) -> Result<Vec<TransactionEnvelope>, ReaderError> {
+    pub fn fee_pool(result: &MetaResult) -> Result<i64, ReaderError> {
         let meta = MetaResultReader::read_meta(result)?;
 
         match meta {
-            LedgerCloseMeta::V0(v0) => Ok(v0.tx_set.txs.to_vec()),
+            LedgerCloseMeta::V0(v0) => {
+                Ok(v0.ledger_header.header.fee_pool)
+            }
             LedgerCloseMeta::V1(v1) => {
-                let mut envelopes = Vec::with_capacity(Self::count_transactions(result)?);
-                 
-                match &v1.tx_set {
-                    GeneralizedTransactionSet::V1(v1) => {
-                        for phase in v1.phases.iter() {
-                            match phase {
-                                TransactionPhase::V0(v0) => {
-                                    for component in v0.iter() {
-                                        match component {
-                                            TxSetComponent::TxsetCompTxsMaybeDiscountedFee(txset) => {
-                                                envelopes.append(&mut txset.txs.to_vec())
-                                            }
-                                        }
-                                    }
-                                }
+                Ok(v1.ledger_header.header.fee_pool)
+            }
+        }
+    }
 
-                                TransactionPhase::V1(v1) => {
-                                    for stage in v1.execution_stages.to_vec() {
-                                        for thread in stage.0.to_vec() {
-                                            envelopes.append(&mut thread.0.to_vec());
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    
-                }
-                Ok(envelopes)
+    pub fn id_pool(result: &MetaResult) -> Result<u64, ReaderError> {
+        let meta = MetaResultReader::read_meta(result)?;
+
+        match meta {
+            LedgerCloseMeta::V0(v0) => {
+                Ok(v0.ledger_header.header.id_pool)
+            }
+            LedgerCloseMeta::V1(v1) => {
+                Ok(v1.ledger_header.header.id_pool)
+            }
+        }
+    }
+
+    /// Returns the skip list of the ledger header.
+    ///
+    /// stellar-core stores in each slot the bucket list hash of an older
+    /// ledger, at increasing distances of 50, 5000, 50000 and 500000 ledgers.
+    pub fn skip_list(result: &MetaResult) -> Result<[[u8; 32]; 4], ReaderError> {
+        let meta = MetaResultReader::read_meta(result)?;
+
+        let skip_list = match meta {
+            LedgerCloseMeta::V0(v0) => &v0.ledger_header.header.skip_list,
+            LedgerCloseMeta::V1(v1) => &v1.ledger_header.header.skip_list,
+        };
+
+        Ok(skip_list.clone().map(|hash| hash.0))
+    }
+
+    /// Checks the skip list of the ledger against a known older ledger.
+    ///
+    /// # Arguments
+    ///
+    /// * `result` - The ledger whose skip list is checked.
+    /// * `known` - The sequence of the known ledger and its bucket list hash,
+    ///   which is what skip list slots store.
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the skip list slot referencing the known ledger holds
+    /// its hash, `false` if it holds another hash or no slot references it.
+    pub fn verify_skip_list_against_known_ledger(
+        result: &MetaResult,
+        known: (u32, [u8; 32]),
+    ) -> Result<bool, ReaderError> {
+        let sequence = Self::ledger_sequence(result)?;
+        let skip_list = Self::skip_list(result)?;
+
+        Ok(Self::skip_list_sequences(sequence)
+            .into_iter()
+            .zip(skip_list)
+            .any(|(slot_sequence, hash)| slot_sequence == Some(known.0) && hash == known.1))
+    }
+
+    // Mirrors stellar-core's `updateSkipList`: slot `i` is refreshed from slot
+    // `i - 1` once every `SKIP_LIST_STEPS[i]` ledgers, so it lags behind by the
+    // sum of the previous steps.
+    fn skip_list_sequences(sequence: u32) -> [Option<u32>; 4] {
+        const SKIP_LIST_STEPS: [u32; 4] = [50, 5_000, 50_000, 500_000];
+
+        let mut lag = 0;
+        SKIP_LIST_STEPS.map(|step| {
+            let slot_sequence = sequence
+                .checked_sub(lag)
+                .map(|sequence| sequence / step * step)
+                .filter(|slot_sequence| *slot_sequence > 0);
+            lag += step;
+
+            slot_sequence
+        })
+    }
+
+    pub fn count_transactions(result: &MetaResult) -> Result<usize, ReaderError> {
+        let meta = MetaResultReader::read_meta(result)?;
+
+        match meta {
+            LedgerCloseMeta::V0(v0) => {
+                Ok(v0.tx_processing.len())
+            }
+            LedgerCloseMeta::V1(v1) => {
+                Ok(v1.tx_processing.len())
             }
           
         }
     }
 
+    /// Returns the transaction envelopes in the ledger.
+    ///
+    /// Fee bump transactions are returned as their outer envelope only, use
+    /// `fee_bump_inner_transactions` to get the transactions they wrap.
+    pub fn transaction_envelopes(result: &MetaResult) -> Result<Vec<TransactionEnvelope>, ReaderError> {
+        let meta = MetaResultReader::read_meta(result)?;
+
+        Ok(PhaseReader::phases(meta)
+            .into_iter()
+            .flat_map(|phase| phase.transactions)
+            .collect())
+    }
+
+    /// Returns the amount of transactions in each phase of the transaction set,
+    /// keyed by phase index (`0` for classic, `1` for Soroban).
+    ///
+    /// `LedgerCloseMeta::V0` transaction sets have no phases, so all their
+    /// transactions are counted in phase `0`.
+    pub fn transaction_count_by_phase(result: &MetaResult) -> Result<HashMap<u32, usize>, ReaderError> {
+        let meta = MetaResultReader::read_meta(result)?;
+
+        Ok(PhaseReader::phases(meta)
+            .into_iter()
+            .map(|phase| (phase.phase_index, phase.transactions.len()))
+            .collect())
+    }
+
     pub fn transaction_metas(result: &MetaResult) -> Result<Vec<TransactionResultMeta>, ReaderError> {
         let meta = MetaResultReader::read_meta(result)?;
 
@@ -184,16 +395,1911 @@ impl LedgerCloseMetaReader {
         Ok(contract_events)
     }
 
-}
+    /// Returns the contract events in the ledger whose topics start with
+    /// `topic_prefix`. An empty prefix matches every event.
+    pub fn soroban_events_by_topic(result: &MetaResult, topic_prefix: &[ScVal]) -> Result<Vec<ContractEvent>, ReaderError> {
+        let events = Self::soroban_events(result)?
+            .into_iter()
+            .filter(|event| match &event.body {
+                ContractEventBody::V0(v0) => v0.topics.starts_with(topic_prefix),
+            })
+            .collect();
 
-pub struct MetaResultReader;
+        Ok(events)
+    }
 
-impl MetaResultReader {
-    pub fn read_meta(result: &MetaResult) -> Result<&LedgerCloseMeta, ReaderError> {
-        if let Some(meta) = &result.ledger_close_meta {
-            Ok(&meta.ledger_close_meta)
-        } else {
-            Err(ReaderError::MetaResult(result.err.clone().unwrap()))
+    /// Returns the contract events in the ledger, each paired with the
+    /// transaction that emitted it.
+    pub fn soroban_events_with_transaction_context(result: &MetaResult) -> Result<Vec<(ContractEvent, TransactionContext)>, ReaderError> {
+        let mut events = Vec::new();
+
+        for (tx_index, result_meta) in Self::transaction_metas(result)?.into_iter().enumerate() {
+            if let TransactionMeta::V3(v3) = result_meta.tx_apply_processing {
+                if let Some(soroban_meta) = v3.soroban_meta {
+                    let context = TransactionContext {
+                        tx_index,
+                        tx_hash: result_meta.result.transaction_hash.0,
+                        is_successful: matches!(
+                            result_meta.result.result.result,
+                            TransactionResultResult::TxSuccess(_) | TransactionResultResult::TxFeeBumpInnerSuccess(_)
+                        ),
+                    };
+
+                    events.extend(soroban_meta.events.iter().map(|event| (event.clone(), context)));
+                }
+            }
+        }
+
+        Ok(events)
+    }
+
+    /// Returns the inner transactions wrapped by the fee bump envelopes in the
+    /// ledger.
+    pub fn fee_bump_inner_transactions(result: &MetaResult) -> Result<Vec<TransactionEnvelope>, ReaderError> {
+        let mut inner_transactions = Vec::new();
+
+        for envelope in Self::transaction_envelopes(result)? {
+            if let TransactionEnvelope::TxFeeBump(fee_bump) = envelope {
+                match fee_bump.tx.inner_tx {
+                    FeeBumpTransactionInnerTx::Tx(inner) => {
+                        inner_transactions.push(TransactionEnvelope::Tx(inner))
+                    }
+                }
+            }
+        }
+
+        Ok(inner_transactions)
+    }
+
+    /// Returns each transaction result meta in the ledger paired with its
+    /// envelope, in the order the transactions were applied.
+    ///
+    /// The pairing is stable across V0 and V1 meta. It isn't done by zipping
+    /// `transaction_envelopes` and `transaction_metas` by index: envelopes are
+    /// stored in the transaction set order (split in phases for V1) while the
+    /// result metas are in apply order, so zipping them misaligns the pairs as
+    /// soon as the two orders differ. Envelopes are matched by transaction
+    /// hash instead, which is why this takes a `network` argument: the hash
+    /// depends on the network the ledger was closed on.
+    ///
+    /// # Arguments
+    ///
+    /// * `result` - The ledger to read.
+    /// * `network` - The network the ledger was closed on.
+    ///
+    /// # Returns
+    ///
+    /// Returns the pairs, or `ReaderError::MissingEnvelope` if a result meta
+    /// has no envelope with its hash, e.g. when `network` is wrong.
+    pub fn transaction_envelopes_with_results(result: &MetaResult, network: &SupportedNetwork) -> Result<Vec<(TransactionEnvelope, TransactionResultMeta)>, ReaderError> {
+        let envelopes = Self::transaction_envelopes(result)?;
+        let result_metas = Self::transaction_metas(result)?;
+        debug_assert_eq!(envelopes.len(), result_metas.len());
+
+        let network_id = Hash(Sha256::digest(network.passphrase()).into());
+        let mut envelopes_by_hash = HashMap::with_capacity(envelopes.len());
+        for envelope in envelopes {
+            envelopes_by_hash.insert(Self::envelope_hash(&envelope, &network_id)?, envelope);
+        }
+
+        let mut pairs = Vec::with_capacity(result_metas.len());
+        for result_meta in result_metas {
+            let hash = result_meta.result.transaction_hash.0;
+            match envelopes_by_hash.remove(&hash) {
+                Some(envelope) => pairs.push((envelope, result_meta)),
+                None => return Err(ReaderError::MissingEnvelope(hash)),
+            }
+        }
+
+        Ok(pairs)
+    }
+
+    /// Returns a view of each transaction in the ledger, in the order the
+    /// transactions were applied. See `transaction_envelopes_with_results`
+    /// for `network`.
+    pub fn transactions<'a>(
+        result: &'a MetaResult,
+        network: &SupportedNetwork,
+    ) -> Result<Vec<TransactionView<'a>>, ReaderError> {
+        let meta = MetaResultReader::read_meta(result)?;
+        let result_metas = match meta {
+            LedgerCloseMeta::V0(v0) => v0.tx_processing.as_slice(),
+            LedgerCloseMeta::V1(v1) => v1.tx_processing.as_slice(),
+        };
+
+        let network_id = Hash(Sha256::digest(network.passphrase()).into());
+        let mut envelopes_by_hash = HashMap::with_capacity(result_metas.len());
+        for (_, envelopes) in TxSetReader(meta).components() {
+            for envelope in envelopes {
+                envelopes_by_hash.insert(Self::envelope_hash(envelope, &network_id)?, envelope);
+            }
+        }
+
+        let mut transactions = Vec::with_capacity(result_metas.len());
+        for result_meta in result_metas {
+            let hash = result_meta.result.transaction_hash.0;
+            let envelope = envelopes_by_hash
+                .remove(&hash)
+                .ok_or(ReaderError::MissingEnvelope(hash))?;
+            let soroban_meta = match &result_meta.tx_apply_processing {
+                TransactionMeta::V3(v3) => v3.soroban_meta.as_ref(),
+                _ => None,
+            };
+
+            transactions.push(TransactionView {
+                envelope,
+                result: result_meta,
+                soroban_meta,
+            });
+        }
+
+        Ok(transactions)
+    }
+
+    /// Returns every operation in the ledger, regardless of the transaction
+    /// it belongs to. Fee bump envelopes yield the operations of their inner
+    /// transaction.
+    pub fn all_operations_flat(result: &MetaResult) -> Result<Vec<Operation>, ReaderError> {
+        let mut operations = Vec::new();
+
+        for envelope in Self::transaction_envelopes(result)? {
+            operations.extend_from_slice(Self::envelope_operations(&envelope));
+        }
+
+        Ok(operations)
+    }
+
+    /// Counts the operations in the ledger grouped by their type.
+    pub fn operations_by_type(result: &MetaResult) -> Result<HashMap<OperationType, usize>, ReaderError> {
+        let mut counts = HashMap::new();
+
+        for operation in Self::all_operations_flat(result)? {
+            *counts.entry(operation.body.discriminant()).or_insert(0) += 1;
+        }
+
+        Ok(counts)
+    }
+
+    pub fn payment_operations(result: &MetaResult) -> Result<Vec<OperationBody>, ReaderError> {
+        Self::operations_of_type(result, OperationType::Payment)
+    }
+
+    pub fn create_account_operations(result: &MetaResult) -> Result<Vec<OperationBody>, ReaderError> {
+        Self::operations_of_type(result, OperationType::CreateAccount)
+    }
+
+    pub fn change_trust_operations(result: &MetaResult) -> Result<Vec<OperationBody>, ReaderError> {
+        Self::operations_of_type(result, OperationType::ChangeTrust)
+    }
+
+    pub fn manage_sell_offer_operations(result: &MetaResult) -> Result<Vec<OperationBody>, ReaderError> {
+        Self::operations_of_type(result, OperationType::ManageSellOffer)
+    }
+
+    pub fn manage_buy_offer_operations(result: &MetaResult) -> Result<Vec<OperationBody>, ReaderError> {
+        Self::operations_of_type(result, OperationType::ManageBuyOffer)
+    }
+
+    pub fn path_payment_strict_send_operations(result: &MetaResult) -> Result<Vec<OperationBody>, ReaderError> {
+        Self::operations_of_type(result, OperationType::PathPaymentStrictSend)
+    }
+
+    pub fn path_payment_strict_receive_operations(result: &MetaResult) -> Result<Vec<OperationBody>, ReaderError> {
+        Self::operations_of_type(result, OperationType::PathPaymentStrictReceive)
+    }
+
+    /// Returns the claimable balance operations in the ledger along with the
+    /// index of their transaction, in the same order as `transaction_envelopes`,
+    /// and their index within the transaction.
+    ///
+    /// These are the `CreateClaimableBalance` and `ClaimClaimableBalance`
+    /// operations, and the `RevokeSponsorship` operations revoking the
+    /// sponsorship of a claimable balance.
+    pub fn claimable_balance_operations(result: &MetaResult) -> Result<Vec<(OperationBody, usize, usize)>, ReaderError> {
+        let mut operations = Vec::new();
+
+        for (tx_index, envelope) in Self::transaction_envelopes(result)?.iter().enumerate() {
+            for (op_index, operation) in Self::envelope_operations(envelope).iter().enumerate() {
+                let is_claimable_balance = match &operation.body {
+                    OperationBody::CreateClaimableBalance(_) | OperationBody::ClaimClaimableBalance(_) => true,
+                    OperationBody::RevokeSponsorship(RevokeSponsorshipOp::LedgerEntry(key)) => {
+                        matches!(key, LedgerKey::ClaimableBalance(_))
+                    }
+                    _ => false,
+                };
+
+                if is_claimable_balance {
+                    operations.push((operation.body.clone(), tx_index, op_index))
+                }
+            }
+        }
+
+        Ok(operations)
+    }
+
+    /// Returns the IDs of the claimable balances created by successful
+    /// `CreateClaimableBalance` operations in the ledger.
+    pub fn claimable_balance_ids_created(result: &MetaResult) -> Result<Vec<ClaimableBalanceId>, ReaderError> {
+        let mut balance_ids = Vec::new();
+
+        for result_meta in Self::transaction_metas(result)? {
+            for operation_result in Self::operation_results(&result_meta.result.result.result) {
+                if let OperationResult::OpInner(OperationResultTr::CreateClaimableBalance(
+                    CreateClaimableBalanceResult::Success(balance_id),
+                )) = operation_result
+                {
+                    balance_ids.push(balance_id.clone())
+                }
+            }
+        }
+
+        Ok(balance_ids)
+    }
+
+    /// Returns the `RevokeSponsorship` operations in the ledger along with
+    /// their index in `all_operations_flat`.
+    pub fn sponsorship_operations(result: &MetaResult) -> Result<Vec<(RevokeSponsorshipOp, usize)>, ReaderError> {
+        let mut operations = Vec::new();
+
+        for (index, operation) in Self::all_operations_flat(result)?.into_iter().enumerate() {
+            if let OperationBody::RevokeSponsorship(op) = operation.body {
+                operations.push((op, index))
+            }
+        }
+
+        Ok(operations)
+    }
+
+    /// Returns the `BeginSponsoringFutureReserves` operations in the ledger
+    /// along with their index in `all_operations_flat`.
+    pub fn begin_sponsoring_future_reserves_operations(result: &MetaResult) -> Result<Vec<(BeginSponsoringFutureReservesOp, usize)>, ReaderError> {
+        let mut operations = Vec::new();
+
+        for (index, operation) in Self::all_operations_flat(result)?.into_iter().enumerate() {
+            if let OperationBody::BeginSponsoringFutureReserves(op) = operation.body {
+                operations.push((op, index))
+            }
+        }
+
+        Ok(operations)
+    }
+
+    /// Returns the index in `all_operations_flat` of the
+    /// `EndSponsoringFutureReserves` operations in the ledger. These
+    /// operations have no body.
+    pub fn end_sponsoring_future_reserves_operations(result: &MetaResult) -> Result<Vec<usize>, ReaderError> {
+        Ok(Self::all_operations_flat(result)?
+            .iter()
+            .enumerate()
+            .filter(|(_, operation)| matches!(operation.body, OperationBody::EndSponsoringFutureReserves))
+            .map(|(index, _)| index)
+            .collect())
+    }
+
+    /// Returns the `LiquidityPoolDeposit` operations in the ledger paired
+    /// with the result meta of their transaction, in the order the
+    /// transactions were applied. See `transaction_envelopes_with_results`
+    /// for `network`.
+    pub fn liquidity_pool_deposits(result: &MetaResult, network: &SupportedNetwork) -> Result<Vec<(LiquidityPoolDepositOp, TransactionResultMeta)>, ReaderError> {
+        let mut deposits = Vec::new();
+
+        for (envelope, result_meta) in Self::transaction_envelopes_with_results(result, network)? {
+            for operation in Self::envelope_operations(&envelope) {
+                if let OperationBody::LiquidityPoolDeposit(op) = &operation.body {
+                    deposits.push((op.clone(), result_meta.clone()))
+                }
+            }
+        }
+
+        Ok(deposits)
+    }
+
+    /// Returns the `LiquidityPoolWithdraw` operations in the ledger paired
+    /// with the result meta of their transaction, in the order the
+    /// transactions were applied. See `transaction_envelopes_with_results`
+    /// for `network`.
+    pub fn liquidity_pool_withdrawals(result: &MetaResult, network: &SupportedNetwork) -> Result<Vec<(LiquidityPoolWithdrawOp, TransactionResultMeta)>, ReaderError> {
+        let mut withdrawals = Vec::new();
+
+        for (envelope, result_meta) in Self::transaction_envelopes_with_results(result, network)? {
+            for operation in Self::envelope_operations(&envelope) {
+                if let OperationBody::LiquidityPoolWithdraw(op) = &operation.body {
+                    withdrawals.push((op.clone(), result_meta.clone()))
+                }
+            }
+        }
+
+        Ok(withdrawals)
+    }
+
+    /// Returns the changes of sponsor of the ledger entries updated in the
+    /// ledger, in the same order as `ledger_changes`.
+    ///
+    /// Only the sponsor of the entries themselves is tracked, not the sponsors
+    /// of account signers.
+    pub fn sponsorship_changes(result: &MetaResult) -> Result<Vec<SponsorshipChange>, ReaderError> {
+        let mut changes = Vec::new();
+        let mut last_state: Option<LedgerEntry> = None;
+
+        for change in Self::ledger_changes(result)? {
+            match change {
+                LedgerEntryChange::State(state) => {
+                    last_state = Some(state);
+                    continue;
+                }
+                LedgerEntryChange::Updated(updated) => {
+                    if let Some(old) = last_state.take() {
+                        let change = match (Self::sponsor(&old), Self::sponsor(&updated)) {
+                            (None, Some(sponsor)) => Some(SponsorshipChange::Sponsored {
+                                entry: updated.data,
+                                sponsor,
+                            }),
+                            (Some(former_sponsor), None) => Some(SponsorshipChange::Revoked {
+                                entry: updated.data,
+                                former_sponsor,
+                            }),
+                            (Some(old_sponsor), Some(new_sponsor)) if old_sponsor != new_sponsor => {
+                                Some(SponsorshipChange::Transferred {
+                                    entry: updated.data,
+                                    old_sponsor,
+                                    new_sponsor,
+                                })
+                            }
+                            _ => None,
+                        };
+
+                        changes.extend(change);
+                    }
+                }
+                _ => (),
+            }
+            last_state = None;
+        }
+
+        Ok(changes)
+    }
+
+    /// Returns the set of accounts mentioned in the ledger, either as
+    /// transaction or operation source, as payment or create account
+    /// destination, or as address in a soroban authorization entry.
+    pub fn account_ids_involved(result: &MetaResult) -> Result<HashSet<AccountId>, ReaderError> {
+        let mut accounts = HashSet::new();
+
+        for envelope in Self::transaction_envelopes(result)? {
+            match &envelope {
+                TransactionEnvelope::TxV0(v0) => {
+                    accounts.insert(AccountId(PublicKey::PublicKeyTypeEd25519(v0.tx.source_account_ed25519.clone())));
+                }
+                TransactionEnvelope::Tx(v1) => {
+                    accounts.insert(Self::muxed_account_id(&v1.tx.source_account));
+                }
+                TransactionEnvelope::TxFeeBump(fee_bump) => {
+                    accounts.insert(Self::muxed_account_id(&fee_bump.tx.fee_source));
+                    match &fee_bump.tx.inner_tx {
+                        FeeBumpTransactionInnerTx::Tx(inner) => {
+                            accounts.insert(Self::muxed_account_id(&inner.tx.source_account));
+                        }
+                    }
+                }
+            }
+
+            for operation in Self::envelope_operations(&envelope) {
+                if let Some(source) = &operation.source_account {
+                    accounts.insert(Self::muxed_account_id(source));
+                }
+
+                match &operation.body {
+                    OperationBody::Payment(payment) => {
+                        accounts.insert(Self::muxed_account_id(&payment.destination));
+                    }
+                    OperationBody::CreateAccount(create_account) => {
+                        accounts.insert(create_account.destination.clone());
+                    }
+                    OperationBody::InvokeHostFunction(invoke) => {
+                        for entry in invoke.auth.iter() {
+                            if let SorobanCredentials::Address(credentials) = &entry.credentials {
+                                if let ScAddress::Account(account) = &credentials.address {
+                                    accounts.insert(account.clone());
+                                }
+                            }
+                        }
+                    }
+                    _ => (),
+                }
+            }
         }
+
+        Ok(accounts)
+    }
+
+    /// Returns the contracts invoked in the ledger, deduplicated and in order of
+    /// appearance. Contracts that emitted events, for example when called by
+    /// another contract, are included too.
+    pub fn contract_ids_invoked(result: &MetaResult) -> Result<Vec<Hash>, ReaderError> {
+        let mut seen = HashSet::new();
+        let mut contract_ids = Vec::new();
+
+        for operation in Self::all_operations_flat(result)? {
+            if let OperationBody::InvokeHostFunction(invoke) = operation.body {
+                if let HostFunction::InvokeContract(args) = invoke.host_function {
+                    if let ScAddress::Contract(contract_id) = args.contract_address {
+                        if seen.insert(contract_id.clone()) {
+                            contract_ids.push(contract_id)
+                        }
+                    }
+                }
+            }
+        }
+
+        for event in Self::soroban_events(result)? {
+            if let Some(contract_id) = event.contract_id {
+                if seen.insert(contract_id.clone()) {
+                    contract_ids.push(contract_id)
+                }
+            }
+        }
+
+        Ok(contract_ids)
+    }
+
+    /// Returns the soroban authorization entries of each transaction in the
+    /// ledger, in the same order as `transaction_envelopes`. Transactions without
+    /// host function invocations have no entries.
+    pub fn soroban_authorizations(result: &MetaResult) -> Result<Vec<Vec<SorobanAuthorizationEntry>>, ReaderError> {
+        let mut authorizations = Vec::new();
+
+        for envelope in Self::transaction_envelopes(result)? {
+            let mut entries = Vec::new();
+            for operation in Self::envelope_operations(&envelope) {
+                if let OperationBody::InvokeHostFunction(invoke) = &operation.body {
+                    entries.extend_from_slice(&invoke.auth);
+                }
+            }
+
+            authorizations.push(entries);
+        }
+
+        Ok(authorizations)
+    }
+
+    /// Returns the result code of each transaction in the ledger, in the same
+    /// order as `transaction_metas`.
+    pub fn transaction_result_codes(result: &MetaResult) -> Result<Vec<TransactionResultCode>, ReaderError> {
+        Ok(Self::transaction_metas(result)?
+            .iter()
+            .map(|result_meta| result_meta.result.result.result.discriminant())
+            .collect())
+    }
+
+    /// Returns the fee charged to each transaction in the ledger, in stroops and
+    /// in the same order as `transaction_metas`.
+    pub fn fee_charged_per_transaction(result: &MetaResult) -> Result<Vec<i64>, ReaderError> {
+        Ok(Self::transaction_metas(result)?
+            .iter()
+            .map(|result_meta| result_meta.result.result.fee_charged)
+            .collect())
+    }
+
+    /// Counts the transactions in the ledger grouped by their result code.
+    pub fn result_code_histogram(result: &MetaResult) -> Result<HashMap<TransactionResultCode, usize>, ReaderError> {
+        let mut counts = HashMap::new();
+
+        for code in Self::transaction_result_codes(result)? {
+            *counts.entry(code).or_insert(0) += 1;
+        }
+
+        Ok(counts)
+    }
+
+    /// Returns the result of the inflation operation applied in the ledger,
+    /// or `None` if the ledger has no inflation operation.
+    ///
+    /// Inflation is disabled on Pubnet since protocol 12, but may still be
+    /// found when ingesting older ledgers or other networks.
+    pub fn inflation_result(result: &MetaResult) -> Result<Option<InflationResult>, ReaderError> {
+        let has_inflation = Self::all_operations_flat(result)?
+            .iter()
+            .any(|operation| matches!(operation.body, OperationBody::Inflation));
+        if !has_inflation {
+            return Ok(None);
+        }
+
+        for result_meta in Self::transaction_metas(result)? {
+            for operation_result in Self::operation_results(&result_meta.result.result.result) {
+                if let OperationResult::OpInner(OperationResultTr::Inflation(inflation)) =
+                    operation_result
+                {
+                    return Ok(Some(inflation.clone()));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Returns the footprint of each transaction in the ledger, in the same order
+    /// as `transaction_envelopes`. Non-soroban transactions have no footprint.
+    ///
+    /// The footprint is read from the soroban resources declared in the
+    /// envelope. This is authoritative: the host only grants access to the
+    /// declared entries and the transaction meta doesn't record a footprint.
+    pub fn invoke_host_function_footprints(result: &MetaResult) -> Result<Vec<Option<LedgerFootprint>>, ReaderError> {
+        let mut footprints = Vec::new();
+
+        for envelope in Self::transaction_envelopes(result)? {
+            let ext = match &envelope {
+                TransactionEnvelope::TxV0(_) => None,
+                TransactionEnvelope::Tx(v1) => Some(&v1.tx.ext),
+                TransactionEnvelope::TxFeeBump(fee_bump) => match &fee_bump.tx.inner_tx {
+                    FeeBumpTransactionInnerTx::Tx(inner) => Some(&inner.tx.ext),
+                },
+            };
+
+            let footprint = match ext {
+                Some(TransactionExt::V1(soroban_data)) => Some(soroban_data.resources.footprint.clone()),
+                _ => None,
+            };
+
+            footprints.push(footprint);
+        }
+
+        Ok(footprints)
+    }
+
+    /// Returns the time bounds of each transaction in the ledger, in the same
+    /// order as `transaction_envelopes`. Fee bump envelopes yield the time
+    /// bounds of their inner transaction.
+    pub fn time_bounds(result: &MetaResult) -> Result<Vec<Option<TimeBounds>>, ReaderError> {
+        let mut time_bounds = Vec::new();
+
+        for envelope in Self::transaction_envelopes(result)? {
+            let bounds = match &envelope {
+                TransactionEnvelope::TxV0(v0) => v0.tx.time_bounds.clone(),
+                TransactionEnvelope::Tx(v1) => Self::preconditions_time_bounds(&v1.tx.cond),
+                TransactionEnvelope::TxFeeBump(fee_bump) => match &fee_bump.tx.inner_tx {
+                    FeeBumpTransactionInnerTx::Tx(inner) => Self::preconditions_time_bounds(&inner.tx.cond),
+                },
+            };
+
+            time_bounds.push(bounds);
+        }
+
+        Ok(time_bounds)
+    }
+
+    /// Returns the indices, as in `transaction_envelopes`, of the transactions
+    /// whose time bounds overlap with `[min_time, max_time]`. A `max_time` of `0`
+    /// in the transaction bounds means the transaction has no upper bound.
+    pub fn time_bounds_in_range(result: &MetaResult, min_time: u64, max_time: u64) -> Result<Vec<usize>, ReaderError> {
+        let mut indices = Vec::new();
+
+        for (idx, bounds) in Self::time_bounds(result)?.iter().enumerate() {
+            if let Some(bounds) = bounds {
+                let starts_before_end = bounds.min_time.0 <= max_time;
+                let ends_after_start = bounds.max_time.0 == 0 || bounds.max_time.0 >= min_time;
+
+                if starts_before_end && ends_after_start {
+                    indices.push(idx)
+                }
+            }
+        }
+
+        Ok(indices)
+    }
+
+    /// Returns the memo of each transaction in the ledger, in the same order as
+    /// `transaction_envelopes`. Transactions without a memo yield `Memo::None`.
+    pub fn memos(result: &MetaResult) -> Result<Vec<Memo>, ReaderError> {
+        let mut memos = Vec::new();
+
+        for envelope in Self::transaction_envelopes(result)? {
+            let memo = match envelope {
+                TransactionEnvelope::TxV0(v0) => v0.tx.memo,
+                TransactionEnvelope::Tx(v1) => v1.tx.memo,
+                TransactionEnvelope::TxFeeBump(fee_bump) => match fee_bump.tx.inner_tx {
+                    FeeBumpTransactionInnerTx::Tx(inner) => inner.tx.memo,
+                },
+            };
+
+            memos.push(memo);
+        }
+
+        Ok(memos)
+    }
+
+    /// Returns the indices, as in `transaction_envelopes`, of the transactions
+    /// with a text memo equal to `text`.
+    pub fn transactions_with_memo_text(result: &MetaResult, text: &str) -> Result<Vec<usize>, ReaderError> {
+        let mut indices = Vec::new();
+
+        for (idx, memo) in Self::memos(result)?.iter().enumerate() {
+            if let Memo::Text(memo_text) = memo {
+                if memo_text.as_slice() == text.as_bytes() {
+                    indices.push(idx)
+                }
+            }
+        }
+
+        Ok(indices)
+    }
+
+    fn preconditions_time_bounds(cond: &Preconditions) -> Option<TimeBounds> {
+        match cond {
+            Preconditions::None => None,
+            Preconditions::Time(time_bounds) => Some(time_bounds.clone()),
+            Preconditions::V2(v2) => v2.time_bounds.clone(),
+        }
+    }
+
+    /// Returns the hash of every contract code entry created by a successful
+    /// wasm upload in the ledger. Hashes are read from the ledger entry changes
+    /// recorded in the transaction meta.
+    pub fn wasm_hashes(result: &MetaResult) -> Result<Vec<[u8; 32]>, ReaderError> {
+        let mut hashes = Vec::new();
+
+        for result_meta in Self::transaction_metas(result)? {
+            match result_meta.result.result.result {
+                TransactionResultResult::TxSuccess(_) | TransactionResultResult::TxFeeBumpInnerSuccess(_) => (),
+                _ => continue,
+            }
+
+            for operation_meta in Self::operation_metas(&result_meta.tx_apply_processing) {
+                for change in operation_meta.changes.0.iter() {
+                    if let LedgerEntryChange::Created(entry) = change {
+                        if let LedgerEntryData::ContractCode(code) = &entry.data {
+                            hashes.push(code.hash.0)
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(hashes)
+    }
+
+    /// Returns every ledger entry change in the ledger. For each transaction
+    /// the fee changes come first, followed by the changes before the
+    /// operations, the changes of each operation and the changes after the
+    /// operations.
+    pub fn ledger_changes(result: &MetaResult) -> Result<Vec<LedgerEntryChange>, ReaderError> {
+        let mut changes = Vec::new();
+
+        for result_meta in Self::transaction_metas(result)? {
+            let meta = &result_meta.tx_apply_processing;
+
+            changes.extend_from_slice(&result_meta.fee_processing.0);
+            changes.extend_from_slice(Self::changes_before(meta));
+            for operation_meta in Self::operation_metas(meta) {
+                changes.extend_from_slice(&operation_meta.changes.0);
+            }
+            changes.extend_from_slice(Self::changes_after(meta));
+        }
+
+        Ok(changes)
+    }
+
+    /// Returns the ledger entry changes applied before the operations of each
+    /// transaction in the ledger.
+    pub fn ledger_changes_before(result: &MetaResult) -> Result<Vec<LedgerEntryChange>, ReaderError> {
+        let mut changes = Vec::new();
+
+        for result_meta in Self::transaction_metas(result)? {
+            changes.extend_from_slice(Self::changes_before(&result_meta.tx_apply_processing));
+        }
+
+        Ok(changes)
+    }
+
+    /// Returns the ledger entry changes applied after the operations of each
+    /// transaction in the ledger.
+    pub fn ledger_changes_after(result: &MetaResult) -> Result<Vec<LedgerEntryChange>, ReaderError> {
+        let mut changes = Vec::new();
+
+        for result_meta in Self::transaction_metas(result)? {
+            changes.extend_from_slice(Self::changes_after(&result_meta.tx_apply_processing));
+        }
+
+        Ok(changes)
+    }
+
+    /// Returns the contract data entries created, updated or removed in the
+    /// ledger. Removed entries are reported with their state prior to removal.
+    pub fn contract_data_entries_changed(result: &MetaResult) -> Result<Vec<(ContractDataEntry, LedgerEntryChangeType)>, ReaderError> {
+        let mut entries = Vec::new();
+
+        for (data, change_type) in Self::entries_changed(result)? {
+            if let LedgerEntryData::ContractData(entry) = data {
+                entries.push((entry, change_type))
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// Returns the contract code entries created, updated or removed in the
+    /// ledger. Removed entries are reported with their state prior to removal.
+    pub fn contract_code_entries_changed(result: &MetaResult) -> Result<Vec<(ContractCodeEntry, LedgerEntryChangeType)>, ReaderError> {
+        let mut entries = Vec::new();
+
+        for (data, change_type) in Self::entries_changed(result)? {
+            if let LedgerEntryData::ContractCode(entry) = data {
+                entries.push((entry, change_type))
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// Returns the net change of the XLM balance of `account_id` in the ledger,
+    /// in stroops.
+    ///
+    /// # Arguments
+    ///
+    /// * `result` - The meta result of the ledger.
+    /// * `account_id` - The account to compute the balance change for.
+    ///
+    /// # Returns
+    ///
+    /// Returns the sum of the balance deltas of every change touching the
+    /// account: positive if the account gained XLM, negative if it lost XLM
+    /// and `0` if it wasn't changed. Creating the account counts as gaining
+    /// its starting balance and merging it as losing its remaining balance.
+    pub fn net_xlm_changes_for_account(result: &MetaResult, account_id: &AccountId) -> Result<i64, ReaderError> {
+        let mut net_change = 0;
+
+        for change in StateChangeReader::account_changes(result)? {
+            net_change += match change {
+                ChangeType::Created(new) if &new.account_id == account_id => new.balance,
+                ChangeType::Updated { old, new } if &new.account_id == account_id => new.balance - old.balance,
+                ChangeType::Removed(old) if &old.account_id == account_id => -old.balance,
+                _ => 0,
+            };
+        }
+
+        Ok(net_change)
+    }
+
+    /// Returns the account entries created, updated or removed in the ledger,
+    /// in the same order as `ledger_changes`.
+    pub fn account_entries_changed(result: &MetaResult) -> Result<Vec<AccountEntryChange>, ReaderError> {
+        let changes = StateChangeReader::account_changes(result)?
+            .into_iter()
+            .map(|change| match change {
+                ChangeType::Created(new) => AccountEntryChange {
+                    account_id: new.account_id.clone(),
+                    change_type: LedgerEntryChangeType::Created,
+                    old: None,
+                    new: Some(new),
+                },
+                ChangeType::Updated { old, new } => AccountEntryChange {
+                    account_id: new.account_id.clone(),
+                    change_type: LedgerEntryChangeType::Updated,
+                    old: Some(old),
+                    new: Some(new),
+                },
+                ChangeType::Removed(old) => AccountEntryChange {
+                    account_id: old.account_id.clone(),
+                    change_type: LedgerEntryChangeType::Removed,
+                    old: Some(old),
+                    new: None,
+                },
+            })
+            .collect();
+
+        Ok(changes)
+    }
+
+    /// Returns the trustline entries created, updated or removed in the ledger,
+    /// in the same order as `ledger_changes`.
+    pub fn trustline_entries_changed(result: &MetaResult) -> Result<Vec<TrustLineEntryChange>, ReaderError> {
+        let changes = StateChangeReader::trustline_changes(result)?
+            .into_iter()
+            .map(|change| match change {
+                ChangeType::Created(new) => TrustLineEntryChange {
+                    account_id: new.account_id.clone(),
+                    asset: new.asset.clone(),
+                    change_type: LedgerEntryChangeType::Created,
+                    old: None,
+                    new: Some(new),
+                },
+                ChangeType::Updated { old, new } => TrustLineEntryChange {
+                    account_id: new.account_id.clone(),
+                    asset: new.asset.clone(),
+                    change_type: LedgerEntryChangeType::Updated,
+                    old: Some(old),
+                    new: Some(new),
+                },
+                ChangeType::Removed(old) => TrustLineEntryChange {
+                    account_id: old.account_id.clone(),
+                    asset: old.asset.clone(),
+                    change_type: LedgerEntryChangeType::Removed,
+                    old: Some(old),
+                    new: None,
+                },
+            })
+            .collect();
+
+        Ok(changes)
+    }
+
+    /// Returns the offer entries created, updated or removed in the ledger,
+    /// in the same order as `ledger_changes`.
+    pub fn offer_entries_changed(result: &MetaResult) -> Result<Vec<OfferEntryChange>, ReaderError> {
+        let changes = StateChangeReader::offer_changes(result)?
+            .into_iter()
+            .map(|change| match change {
+                ChangeType::Created(new) => OfferEntryChange {
+                    offer_id: new.offer_id,
+                    change_type: LedgerEntryChangeType::Created,
+                    old: None,
+                    new: Some(new),
+                },
+                ChangeType::Updated { old, new } => OfferEntryChange {
+                    offer_id: new.offer_id,
+                    change_type: LedgerEntryChangeType::Updated,
+                    old: Some(old),
+                    new: Some(new),
+                },
+                ChangeType::Removed(old) => OfferEntryChange {
+                    offer_id: old.offer_id,
+                    change_type: LedgerEntryChangeType::Removed,
+                    old: Some(old),
+                    new: None,
+                },
+            })
+            .collect();
+
+        Ok(changes)
+    }
+
+    /// Returns the liquidity pool entries created, updated or removed in the
+    /// ledger, in the same order as `ledger_changes`.
+    pub fn liquidity_pool_changes(result: &MetaResult) -> Result<Vec<LiquidityPoolEntryChange>, ReaderError> {
+        let changes = StateChangeReader::liquidity_pool_changes(result)?
+            .into_iter()
+            .map(|change| match change {
+                ChangeType::Created(new) => LiquidityPoolEntryChange {
+                    pool_id: new.liquidity_pool_id.clone(),
+                    change_type: LedgerEntryChangeType::Created,
+                    old: None,
+                    new: Some(new),
+                },
+                ChangeType::Updated { old, new } => LiquidityPoolEntryChange {
+                    pool_id: new.liquidity_pool_id.clone(),
+                    change_type: LedgerEntryChangeType::Updated,
+                    old: Some(old),
+                    new: Some(new),
+                },
+                ChangeType::Removed(old) => LiquidityPoolEntryChange {
+                    pool_id: old.liquidity_pool_id.clone(),
+                    change_type: LedgerEntryChangeType::Removed,
+                    old: Some(old),
+                    new: None,
+                },
+            })
+            .collect();
+
+        Ok(changes)
+    }
+
+    /// Returns the contract code entries created in the ledger, i.e. the Wasm
+    /// uploaded to the network. Updated code entries are not included.
+    pub fn soroban_wasm_deployments(result: &MetaResult) -> Result<Vec<ContractCodeEntry>, ReaderError> {
+        let mut deployments = Vec::new();
+
+        for change in Self::ledger_changes(result)? {
+            if let LedgerEntryChange::Created(LedgerEntry { data: LedgerEntryData::ContractCode(entry), .. }) = change {
+                deployments.push(entry)
+            }
+        }
+
+        Ok(deployments)
+    }
+
+    /// Returns the contract instance entries created in the ledger, i.e. the
+    /// contracts instantiated in the ledger.
+    ///
+    /// Instances are the contract data entries keyed by
+    /// `ScVal::LedgerKeyContractInstance`, which hold the contract's executable.
+    pub fn soroban_contract_instantiations(result: &MetaResult) -> Result<Vec<ContractDataEntry>, ReaderError> {
+        let mut instances = Vec::new();
+
+        for change in Self::ledger_changes(result)? {
+            if let LedgerEntryChange::Created(LedgerEntry { data: LedgerEntryData::ContractData(entry), .. }) = change {
+                if entry.key == ScVal::LedgerKeyContractInstance {
+                    instances.push(entry)
+                }
+            }
+        }
+
+        Ok(instances)
+    }
+
+    // Removals only carry the ledger key, so the entry is taken from the `State`
+    // change that stellar-core records right before them.
+    fn entries_changed(result: &MetaResult) -> Result<Vec<(LedgerEntryData, LedgerEntryChangeType)>, ReaderError> {
+        let mut entries = Vec::new();
+        let mut last_state: Option<LedgerEntryData> = None;
+
+        for change in Self::ledger_changes(result)? {
+            match change {
+                LedgerEntryChange::Created(entry) => {
+                    entries.push((entry.data, LedgerEntryChangeType::Created))
+                }
+                LedgerEntryChange::Updated(entry) => {
+                    entries.push((entry.data, LedgerEntryChangeType::Updated))
+                }
+                LedgerEntryChange::Removed(_) => {
+                    if let Some(data) = last_state.take() {
+                        entries.push((data, LedgerEntryChangeType::Removed))
+                    }
+                }
+                LedgerEntryChange::State(entry) => {
+                    last_state = Some(entry.data);
+                    continue;
+                }
+            }
+            last_state = None;
+        }
+
+        Ok(entries)
+    }
+
+    fn changes_before(meta: &TransactionMeta) -> &[LedgerEntryChange] {
+        match meta {
+            TransactionMeta::V0(_) => &[],
+            TransactionMeta::V1(v1) => &v1.tx_changes.0,
+            TransactionMeta::V2(v2) => &v2.tx_changes_before.0,
+            TransactionMeta::V3(v3) => &v3.tx_changes_before.0,
+        }
+    }
+
+    fn changes_after(meta: &TransactionMeta) -> &[LedgerEntryChange] {
+        match meta {
+            TransactionMeta::V0(_) | TransactionMeta::V1(_) => &[],
+            TransactionMeta::V2(v2) => &v2.tx_changes_after.0,
+            TransactionMeta::V3(v3) => &v3.tx_changes_after.0,
+        }
+    }
+
+    fn operation_metas(meta: &TransactionMeta) -> &[OperationMeta] {
+        match meta {
+            TransactionMeta::V0(operations) => operations.as_slice(),
+            TransactionMeta::V1(v1) => v1.operations.as_slice(),
+            TransactionMeta::V2(v2) => v2.operations.as_slice(),
+            TransactionMeta::V3(v3) => v3.operations.as_slice(),
+        }
+    }
+
+    fn envelope_hash(envelope: &TransactionEnvelope, network_id: &Hash) -> Result<[u8; 32], ReaderError> {
+        let tagged_transaction = match envelope {
+            TransactionEnvelope::TxV0(v0) => {
+                let tx = &v0.tx;
+                TransactionSignaturePayloadTaggedTransaction::Tx(Transaction {
+                    source_account: MuxedAccount::Ed25519(tx.source_account_ed25519.clone()),
+                    fee: tx.fee,
+                    seq_num: tx.seq_num.clone(),
+                    cond: match &tx.time_bounds {
+                        Some(time_bounds) => Preconditions::Time(time_bounds.clone()),
+                        None => Preconditions::None,
+                    },
+                    memo: tx.memo.clone(),
+                    operations: tx.operations.clone(),
+                    ext: TransactionExt::V0,
+                })
+            }
+            TransactionEnvelope::Tx(v1) => TransactionSignaturePayloadTaggedTransaction::Tx(v1.tx.clone()),
+            TransactionEnvelope::TxFeeBump(fee_bump) => {
+                TransactionSignaturePayloadTaggedTransaction::TxFeeBump(fee_bump.tx.clone())
+            }
+        };
+
+        let payload = TransactionSignaturePayload {
+            network_id: network_id.clone(),
+            tagged_transaction,
+        };
+        let encoded = payload
+            .to_xdr(Limits::none())
+            .map_err(|error| ReaderError::Encode(error.to_string()))?;
+
+        Ok(Sha256::digest(encoded).into())
+    }
+
+    // Fee bump transactions yield the operation results of their inner transaction.
+    fn operation_results(result: &TransactionResultResult) -> &[OperationResult] {
+        match result {
+            TransactionResultResult::TxSuccess(results)
+            | TransactionResultResult::TxFailed(results) => results.as_slice(),
+            TransactionResultResult::TxFeeBumpInnerSuccess(inner)
+            | TransactionResultResult::TxFeeBumpInnerFailed(inner) => match &inner.result.result {
+                InnerTransactionResultResult::TxSuccess(results)
+                | InnerTransactionResultResult::TxFailed(results) => results.as_slice(),
+                _ => &[],
+            },
+            _ => &[],
+        }
+    }
+
+    fn envelope_operations(envelope: &TransactionEnvelope) -> &[Operation] {
+        match envelope {
+            TransactionEnvelope::TxV0(v0) => v0.tx.operations.as_slice(),
+            TransactionEnvelope::Tx(v1) => v1.tx.operations.as_slice(),
+            TransactionEnvelope::TxFeeBump(fee_bump) => match &fee_bump.tx.inner_tx {
+                FeeBumpTransactionInnerTx::Tx(inner) => inner.tx.operations.as_slice(),
+            },
+        }
+    }
+
+    fn muxed_account_id(account: &MuxedAccount) -> AccountId {
+        let ed25519 = match account {
+            MuxedAccount::Ed25519(ed25519) => ed25519.clone(),
+            MuxedAccount::MuxedEd25519(muxed) => muxed.ed25519.clone(),
+        };
+
+        AccountId(PublicKey::PublicKeyTypeEd25519(ed25519))
+    }
+
+    fn operations_of_type(result: &MetaResult, operation_type: OperationType) -> Result<Vec<OperationBody>, ReaderError> {
+        Ok(Self::all_operations_flat(result)?
+            .into_iter()
+            .map(|operation| operation.body)
+            .filter(|body| body.discriminant() == operation_type)
+            .collect())
+    }
+
+    fn sponsor(entry: &LedgerEntry) -> Option<AccountId> {
+        match &entry.ext {
+            LedgerEntryExt::V0 => None,
+            LedgerEntryExt::V1(v1) => v1.sponsoring_id.0.clone(),
+        }
+    }
+
+}
+
+pub struct MetaResultReader;
+
+impl MetaResultReader {
+    pub fn read_meta(result: &MetaResult) -> Result<&LedgerCloseMeta, ReaderError> {
+        if let Some(meta) = &result.ledger_close_meta {
+            Ok(&meta.ledger_close_meta)
+        } else {
+            Err(ReaderError::MetaResult(result.err.clone().unwrap()))
+        }
+    }
+}
+
+/// Transactions of a single phase of the transaction set.
+#[derive(Clone, Debug)]
+pub struct PhaseView {
+    /// Index of the phase in the transaction set, `0` for classic
+    /// and `1` for Soroban transactions.
+    pub phase_index: u32,
+
+    /// Transaction envelopes of the phase, in transaction set order.
+    pub transactions: Vec<TransactionEnvelope>,
+}
+
+/// Reads the phases of a ledger's transaction set regardless of the
+/// `LedgerCloseMeta`, `TransactionPhase` and `TxSetComponent` versions.
+pub struct PhaseReader;
+
+impl PhaseReader {
+    /// Returns the phases of the transaction set of `meta`.
+    ///
+    /// `LedgerCloseMeta::V0` transaction sets have no phases, so all their
+    /// transactions are returned as phase `0`. Parallel Soroban phases are
+    /// flattened stage by stage and thread by thread.
+    pub fn phases(meta: &LedgerCloseMeta) -> Vec<PhaseView> {
+        match meta {
+            LedgerCloseMeta::V0(v0) => vec![PhaseView {
+                phase_index: 0,
+                transactions: v0.tx_set.txs.to_vec(),
+            }],
+            LedgerCloseMeta::V1(v1) => match &v1.tx_set {
+                GeneralizedTransactionSet::V1(v1) => v1
+                    .phases
+                    .iter()
+                    .enumerate()
+                    .map(|(index, phase)| PhaseView {
+                        phase_index: index as u32,
+                        transactions: Self::phase_transactions(phase),
+                    })
+                    .collect(),
+            },
+        }
+    }
+
+    fn phase_transactions(phase: &TransactionPhase) -> Vec<TransactionEnvelope> {
+        match phase {
+            TransactionPhase::V0(v0) => v0
+                .iter()
+                .flat_map(|component| match component {
+                    TxSetComponent::TxsetCompTxsMaybeDiscountedFee(txset) => txset.txs.iter(),
+                })
+                .cloned()
+                .collect(),
+            TransactionPhase::V1(v1) => v1
+                .execution_stages
+                .iter()
+                .flat_map(|stage| stage.0.iter())
+                .flat_map(|thread| thread.0.iter())
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+/// Reads the components of a ledger's transaction set regardless of
+/// the `LedgerCloseMeta` version.
+///
+/// Components are numbered across phases in order. A `V0` transaction
+/// set is a single component without base fee, and a parallel Soroban
+/// phase is a single component spanning all its execution stages.
+pub struct TxSetReader<'a>(pub &'a LedgerCloseMeta);
+
+impl<'a> TxSetReader<'a> {
+    /// Returns the amount of components in the transaction set.
+    pub fn component_count(&self) -> usize {
+        self.components().len()
+    }
+
+    /// Returns the discounted base fee of the component at `idx`, or `None`
+    /// if the component has no discounted fee or doesn't exist.
+    pub fn base_fee_for_component(&self, idx: usize) -> Option<i64> {
+        self.components()
+            .into_iter()
+            .nth(idx)
+            .and_then(|(base_fee, _)| base_fee)
+    }
+
+    /// Returns the transaction envelopes of the component at `idx`, empty
+    /// if the component doesn't exist.
+    pub fn transactions_for_component(&self, idx: usize) -> Vec<&'a TransactionEnvelope> {
+        self.components()
+            .into_iter()
+            .nth(idx)
+            .map(|(_, transactions)| transactions)
+            .unwrap_or_default()
+    }
+
+    fn components(&self) -> Vec<(Option<i64>, Vec<&'a TransactionEnvelope>)> {
+        match self.0 {
+            LedgerCloseMeta::V0(v0) => vec![(None, v0.tx_set.txs.iter().collect())],
+            LedgerCloseMeta::V1(v1) => match &v1.tx_set {
+                GeneralizedTransactionSet::V1(v1) => v1
+                    .phases
+                    .iter()
+                    .flat_map(|phase| match phase {
+                        TransactionPhase::V0(v0) => v0
+                            .iter()
+                            .map(|component| match component {
+                                TxSetComponent::TxsetCompTxsMaybeDiscountedFee(txset) => {
+                                    (txset.base_fee, txset.txs.iter().collect())
+                                }
+                            })
+                            .collect(),
+                        TransactionPhase::V1(v1) => vec![(
+                            v1.base_fee,
+                            v1.execution_stages
+                                .iter()
+                                .flat_map(|stage| stage.0.iter())
+                                .flat_map(|thread| thread.0.iter())
+                                .collect(),
+                        )],
+                    })
+                    .collect(),
+            },
+        }
+    }
+}
+
+/// Change of the sponsor of an updated ledger entry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SponsorshipChange {
+    /// The entry started being sponsored by `sponsor`.
+    Sponsored { entry: LedgerEntryData, sponsor: AccountId },
+
+    /// The sponsorship of the entry by `former_sponsor` was revoked.
+    Revoked { entry: LedgerEntryData, former_sponsor: AccountId },
+
+    /// The sponsorship of the entry was transferred from `old_sponsor` to `new_sponsor`.
+    Transferred { entry: LedgerEntryData, old_sponsor: AccountId, new_sponsor: AccountId },
+}
+
+/// Change applied to an account entry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccountEntryChange {
+    /// The account that was changed.
+    pub account_id: AccountId,
+
+    /// Kind of change applied to the account.
+    pub change_type: LedgerEntryChangeType,
+
+    /// The account before the change, `None` if it was created.
+    pub old: Option<AccountEntry>,
+
+    /// The account after the change, `None` if it was removed.
+    pub new: Option<AccountEntry>,
+}
+
+/// Change applied to a trustline entry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TrustLineEntryChange {
+    /// The account holding the trustline.
+    pub account_id: AccountId,
+
+    /// The asset of the trustline, either an asset or a liquidity pool share.
+    pub asset: TrustLineAsset,
+
+    /// Kind of change applied to the trustline.
+    pub change_type: LedgerEntryChangeType,
+
+    /// The trustline before the change, `None` if it was created.
+    pub old: Option<TrustLineEntry>,
+
+    /// The trustline after the change, `None` if it was removed.
+    pub new: Option<TrustLineEntry>,
+}
+
+/// Change applied to an offer entry of the built-in DEX.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OfferEntryChange {
+    /// The id of the offer.
+    pub offer_id: i64,
+
+    /// Kind of change applied to the offer.
+    pub change_type: LedgerEntryChangeType,
+
+    /// The offer before the change, `None` if it was created.
+    pub old: Option<OfferEntry>,
+
+    /// The offer after the change, `None` if it was removed (filled or deleted).
+    pub new: Option<OfferEntry>,
+}
+
+/// Change applied to a liquidity pool entry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LiquidityPoolEntryChange {
+    /// The id of the liquidity pool.
+    pub pool_id: PoolId,
+
+    /// Kind of change applied to the liquidity pool.
+    pub change_type: LedgerEntryChangeType,
+
+    /// The liquidity pool before the change, `None` if it was created.
+    pub old: Option<LiquidityPoolEntry>,
+
+    /// The liquidity pool after the change, `None` if it was removed.
+    pub new: Option<LiquidityPoolEntry>,
+}
+
+/// Change applied to a ledger entry of type `T`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChangeType<T> {
+    /// The entry was created with the given state.
+    Created(T),
+
+    /// The entry was updated from `old` to `new`.
+    Updated { old: T, new: T },
+
+    /// The entry was removed, reported with its state prior to removal.
+    Removed(T),
+}
+
+/// Reads the ledger entry changes of a ledger grouped by entry type.
+///
+/// Changes are returned in the same order as [`LedgerCloseMetaReader::ledger_changes`].
+pub struct StateChangeReader;
+
+impl StateChangeReader {
+    /// Returns the changes applied to account entries in the ledger.
+    pub fn account_changes(
+        result: &MetaResult,
+    ) -> Result<Vec<ChangeType<AccountEntry>>, ReaderError> {
+        Self::changes(result, |data| match data {
+            LedgerEntryData::Account(entry) => Some(entry),
+            _ => None,
+        })
+    }
+
+    /// Returns the changes applied to trustline entries in the ledger.
+    pub fn trustline_changes(
+        result: &MetaResult,
+    ) -> Result<Vec<ChangeType<TrustLineEntry>>, ReaderError> {
+        Self::changes(result, |data| match data {
+            LedgerEntryData::Trustline(entry) => Some(entry),
+            _ => None,
+        })
+    }
+
+    /// Returns the changes applied to offer entries in the ledger.
+    pub fn offer_changes(result: &MetaResult) -> Result<Vec<ChangeType<OfferEntry>>, ReaderError> {
+        Self::changes(result, |data| match data {
+            LedgerEntryData::Offer(entry) => Some(entry),
+            _ => None,
+        })
+    }
+
+    /// Returns the changes applied to account data entries in the ledger.
+    pub fn data_changes(result: &MetaResult) -> Result<Vec<ChangeType<DataEntry>>, ReaderError> {
+        Self::changes(result, |data| match data {
+            LedgerEntryData::Data(entry) => Some(entry),
+            _ => None,
+        })
+    }
+
+    /// Returns the changes applied to contract data entries in the ledger.
+    pub fn contract_data_changes(
+        result: &MetaResult,
+    ) -> Result<Vec<ChangeType<ContractDataEntry>>, ReaderError> {
+        Self::changes(result, |data| match data {
+            LedgerEntryData::ContractData(entry) => Some(entry),
+            _ => None,
+        })
+    }
+
+    /// Returns the changes applied to contract code entries in the ledger.
+    pub fn contract_code_changes(
+        result: &MetaResult,
+    ) -> Result<Vec<ChangeType<ContractCodeEntry>>, ReaderError> {
+        Self::changes(result, |data| match data {
+            LedgerEntryData::ContractCode(entry) => Some(entry),
+            _ => None,
+        })
+    }
+
+    /// Returns the changes applied to liquidity pool entries in the ledger.
+    pub fn liquidity_pool_changes(
+        result: &MetaResult,
+    ) -> Result<Vec<ChangeType<LiquidityPoolEntry>>, ReaderError> {
+        Self::changes(result, |data| match data {
+            LedgerEntryData::LiquidityPool(entry) => Some(entry),
+            _ => None,
+        })
+    }
+
+    /// Returns the changes applied to claimable balance entries in the ledger.
+    pub fn claimable_balance_changes(
+        result: &MetaResult,
+    ) -> Result<Vec<ChangeType<ClaimableBalanceEntry>>, ReaderError> {
+        Self::changes(result, |data| match data {
+            LedgerEntryData::ClaimableBalance(entry) => Some(entry),
+            _ => None,
+        })
+    }
+
+    // Updates and removals are preceded by a `State` change holding the entry
+    // as it was before, which provides `old` and the removed entry.
+    fn changes<T>(
+        result: &MetaResult,
+        entry: impl Fn(LedgerEntryData) -> Option<T>,
+    ) -> Result<Vec<ChangeType<T>>, ReaderError> {
+        let mut changes = Vec::new();
+        let mut last_state: Option<T> = None;
+
+        for change in LedgerCloseMetaReader::ledger_changes(result)? {
+            match change {
+                LedgerEntryChange::Created(created) => {
+                    if let Some(new) = entry(created.data) {
+                        changes.push(ChangeType::Created(new))
+                    }
+                }
+                LedgerEntryChange::Updated(updated) => {
+                    if let (Some(old), Some(new)) = (last_state.take(), entry(updated.data)) {
+                        changes.push(ChangeType::Updated { old, new })
+                    }
+                }
+                LedgerEntryChange::Removed(_) => {
+                    if let Some(old) = last_state.take() {
+                        changes.push(ChangeType::Removed(old))
+                    }
+                }
+                LedgerEntryChange::State(state) => {
+                    last_state = entry(state.data);
+                    continue;
+                }
+            }
+            last_state = None;
+        }
+
+        Ok(changes)
+    }
+}
+
+/// The transaction that emitted a contract event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TransactionContext {
+    /// Index of the transaction in the ledger's transaction metas.
+    pub tx_index: usize,
+
+    /// Hash of the transaction.
+    pub tx_hash: [u8; 32],
+
+    /// Whether the transaction was applied successfully.
+    pub is_successful: bool,
+}
+
+/// A transaction in a ledger along with its result and soroban meta.
+#[derive(Clone, Copy, Debug)]
+pub struct TransactionView<'a> {
+    /// Envelope of the transaction.
+    pub envelope: &'a TransactionEnvelope,
+
+    /// Result and meta recorded when applying the transaction.
+    pub result: &'a TransactionResultMeta,
+
+    /// Soroban meta of the transaction, `None` for classic transactions.
+    pub soroban_meta: Option<&'a SorobanTransactionMeta>,
+}
+
+impl<'a> TransactionView<'a> {
+    /// Returns whether the transaction was applied successfully. Fee bump
+    /// transactions are successful if their inner transaction is.
+    pub fn is_successful(&self) -> bool {
+        matches!(
+            self.result.result.result.result,
+            TransactionResultResult::TxSuccess(_) | TransactionResultResult::TxFeeBumpInnerSuccess(_)
+        )
+    }
+
+    /// Returns the fee charged to the transaction source account, in stroops.
+    pub fn fee_charged(&self) -> i64 {
+        self.result.result.result.fee_charged
+    }
+
+    /// Returns the operations of the transaction. Fee bump envelopes yield
+    /// the operations of their inner transaction.
+    pub fn operations(&self) -> &'a [Operation] {
+        LedgerCloseMetaReader::envelope_operations(self.envelope)
+    }
+
+    /// Returns the contract events emitted by the transaction.
+    pub fn soroban_events(&self) -> &'a [ContractEvent] {
+        match self.soroban_meta {
+            Some(soroban_meta) => soroban_meta.events.as_slice(),
+            None => &[],
+        }
+    }
+
+    /// Returns the hash of the transaction.
+    pub fn hash(&self) -> [u8; 32] {
+        self.result.result.transaction_hash.0
+    }
+}
+
+/// Iterates the contract events of a ledger without collecting them.
+///
+/// Unlike `LedgerCloseMetaReader::soroban_events`, events are borrowed from the
+/// meta and filtered lazily, so ledgers without events cost no allocation.
+#[derive(Clone, Copy, Debug)]
+pub struct SorobanEventReader<'a>(pub &'a LedgerCloseMeta);
+
+impl<'a> SorobanEventReader<'a> {
+    /// Returns an iterator over the contract events in the ledger, in the
+    /// order the transactions were applied.
+    pub fn events_iter(&self) -> impl Iterator<Item = &'a ContractEvent> + 'a {
+        let result_metas = match self.0 {
+            LedgerCloseMeta::V0(v0) => v0.tx_processing.as_slice(),
+            LedgerCloseMeta::V1(v1) => v1.tx_processing.as_slice(),
+        };
+
+        result_metas
+            .iter()
+            .filter_map(|result_meta| match &result_meta.tx_apply_processing {
+                TransactionMeta::V3(v3) => v3.soroban_meta.as_ref(),
+                _ => None,
+            })
+            .flat_map(|soroban_meta| soroban_meta.events.iter())
+    }
+
+    /// Returns an iterator over the contract events emitted by `contract_id`.
+    pub fn filter_by_contract<'b>(
+        &self,
+        contract_id: &'b Hash,
+    ) -> impl Iterator<Item = &'a ContractEvent> + 'b
+    where
+        'a: 'b,
+    {
+        self.events_iter()
+            .filter(move |event| event.contract_id.as_ref() == Some(contract_id))
+    }
+
+    /// Returns an iterator over the contract events whose topics start with
+    /// `prefix`. An empty prefix matches every event.
+    pub fn filter_by_topic<'b>(
+        &self,
+        prefix: &'b [ScVal],
+    ) -> impl Iterator<Item = &'a ContractEvent> + 'b
+    where
+        'a: 'b,
+    {
+        self.events_iter().filter(move |event| match &event.body {
+            ContractEventBody::V0(v0) => v0.topics.starts_with(prefix),
+        })
+    }
+}
+
+/// Iterates the operations of a ledger without collecting them.
+pub struct OperationReader;
+
+impl OperationReader {
+    /// Returns an iterator over every operation in the ledger, in transaction
+    /// set order. Fee bump envelopes yield the operations of their inner
+    /// transaction.
+    pub fn iter_all(result: &MetaResult) -> Result<impl Iterator<Item = &Operation>, ReaderError> {
+        let meta = MetaResultReader::read_meta(result)?;
+
+        // Only one of the two slices is non-empty, which lets both ledger
+        // versions share the same iterator type.
+        let (v0_envelopes, phases): (&[TransactionEnvelope], &[TransactionPhase]) = match meta {
+            LedgerCloseMeta::V0(v0) => (v0.tx_set.txs.as_slice(), &[]),
+            LedgerCloseMeta::V1(v1) => match &v1.tx_set {
+                GeneralizedTransactionSet::V1(v1) => (&[], v1.phases.as_slice()),
+            },
+        };
+
+        Ok(v0_envelopes
+            .iter()
+            .chain(phases.iter().flat_map(Self::phase_envelopes))
+            .flat_map(LedgerCloseMetaReader::envelope_operations))
+    }
+
+    /// Returns an iterator over the bodies of the operations of type `T` in
+    /// the ledger, e.g. `OperationReader::iter_by_type::<PaymentOp>(&result)`.
+    pub fn iter_by_type<T: OperationBodyMatch>(
+        result: &MetaResult,
+    ) -> Result<impl Iterator<Item = &T>, ReaderError> {
+        Ok(Self::iter_all(result)?.filter_map(|operation| T::match_body(&operation.body)))
+    }
+
+    fn phase_envelopes(phase: &TransactionPhase) -> impl Iterator<Item = &TransactionEnvelope> {
+        let (components, stages) = match phase {
+            TransactionPhase::V0(v0) => (v0.as_slice(), &[][..]),
+            TransactionPhase::V1(v1) => (&[][..], v1.execution_stages.as_slice()),
+        };
+
+        components
+            .iter()
+            .flat_map(|component| match component {
+                TxSetComponent::TxsetCompTxsMaybeDiscountedFee(txset) => txset.txs.iter(),
+            })
+            .chain(
+                stages
+                    .iter()
+                    .flat_map(|stage| stage.0.iter())
+                    .flat_map(|thread| thread.0.iter()),
+            )
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Operation payloads that can be matched out of an `OperationBody`.
+///
+/// This trait is sealed and implemented for the payload of every
+/// `OperationBody` variant except `AccountMerge`, whose payload is a plain
+/// `MuxedAccount`, and the payload-less `Inflation` and
+/// `EndSponsoringFutureReserves`.
+pub trait OperationBodyMatch: sealed::Sealed + Sized + 'static {
+    /// Returns the payload of `body` if it is an operation of this type.
+    fn match_body(body: &OperationBody) -> Option<&Self>;
+}
+
+macro_rules! operation_body_match {
+    ($($variant:ident => $op:ident),* $(,)?) => {
+        $(
+            impl sealed::Sealed for stellar_xdr::next::$op {}
+
+            impl OperationBodyMatch for stellar_xdr::next::$op {
+                fn match_body(body: &OperationBody) -> Option<&Self> {
+                    match body {
+                        OperationBody::$variant(op) => Some(op),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+operation_body_match!(
+    CreateAccount => CreateAccountOp,
+    Payment => PaymentOp,
+    PathPaymentStrictReceive => PathPaymentStrictReceiveOp,
+    ManageSellOffer => ManageSellOfferOp,
+    CreatePassiveSellOffer => CreatePassiveSellOfferOp,
+    SetOptions => SetOptionsOp,
+    ChangeTrust => ChangeTrustOp,
+    AllowTrust => AllowTrustOp,
+    ManageData => ManageDataOp,
+    BumpSequence => BumpSequenceOp,
+    ManageBuyOffer => ManageBuyOfferOp,
+    PathPaymentStrictSend => PathPaymentStrictSendOp,
+    CreateClaimableBalance => CreateClaimableBalanceOp,
+    ClaimClaimableBalance => ClaimClaimableBalanceOp,
+    BeginSponsoringFutureReserves => BeginSponsoringFutureReservesOp,
+    RevokeSponsorship => RevokeSponsorshipOp,
+    Clawback => ClawbackOp,
+    ClawbackClaimableBalance => ClawbackClaimableBalanceOp,
+    SetTrustLineFlags => SetTrustLineFlagsOp,
+    LiquidityPoolDeposit => LiquidityPoolDepositOp,
+    LiquidityPoolWithdraw => LiquidityPoolWithdrawOp,
+    InvokeHostFunction => InvokeHostFunctionOp,
+    ExtendFootprintTtl => ExtendFootprintTtlOp,
+    RestoreFootprint => RestoreFootprintOp,
+);
+
+/// Reads the header fields of an already decoded ledger without touching its
+/// transaction set or transaction metas.
+pub struct HeaderReader;
+
+impl HeaderReader {
+    /// Returns the sequence of the ledger.
+    pub fn sequence(meta: &LedgerCloseMeta) -> u32 {
+        Self::header(meta).ledger_seq
+    }
+
+    /// Returns the close time of the ledger, in seconds since the unix epoch.
+    pub fn close_time(meta: &LedgerCloseMeta) -> u64 {
+        Self::header(meta).scp_value.close_time.0
+    }
+
+    /// Returns the base fee of the ledger, in stroops.
+    pub fn base_fee(meta: &LedgerCloseMeta) -> u32 {
+        Self::header(meta).base_fee
+    }
+
+    /// Returns the base reserve of the ledger, in stroops.
+    pub fn base_reserve(meta: &LedgerCloseMeta) -> u32 {
+        Self::header(meta).base_reserve
+    }
+
+    /// Returns the total amount of lumens in existence, in stroops.
+    pub fn total_coins(meta: &LedgerCloseMeta) -> i64 {
+        Self::header(meta).total_coins
+    }
+
+    /// Returns the fees burned since the last inflation run, in stroops.
+    pub fn fee_pool(meta: &LedgerCloseMeta) -> i64 {
+        Self::header(meta).fee_pool
+    }
+
+    /// Returns the protocol version of the ledger.
+    pub fn ledger_version(meta: &LedgerCloseMeta) -> u32 {
+        Self::header(meta).ledger_version
+    }
+
+    /// Returns the hash of the bucket list after the ledger closed.
+    pub fn bucket_list_hash(meta: &LedgerCloseMeta) -> [u8; 32] {
+        Self::header(meta).bucket_list_hash.0
+    }
+
+    /// Returns the amount of transactions applied in the ledger.
+    pub fn tx_count(meta: &LedgerCloseMeta) -> usize {
+        match meta {
+            LedgerCloseMeta::V0(v0) => v0.tx_processing.len(),
+            LedgerCloseMeta::V1(v1) => v1.tx_processing.len(),
+        }
+    }
+
+    fn header(meta: &LedgerCloseMeta) -> &LedgerHeader {
+        match meta {
+            LedgerCloseMeta::V0(v0) => &v0.ledger_header.header,
+            LedgerCloseMeta::V1(v1) => &v1.ledger_header.header,
+        }
+    }
+}
+
+/// Computes fee statistics over the transactions of a batch of ledgers, e.g.
+/// the results received from [`CaptiveCore::prepare_ledgers_multi_thread`](crate::CaptiveCore::prepare_ledgers_multi_thread).
+///
+/// All fees are the fees charged to the transactions, in stroops. End
+/// sentinels (see [`MetaResult::is_catchup_end_sentinel`]) in the batch are
+/// skipped, other errors are returned.
+pub struct FeeReader;
+
+impl FeeReader {
+    /// Returns the median fee charged. For an even amount of transactions this
+    /// is the mean of the two middle fees.
+    pub fn median_fee_charged(results: &[MetaResult]) -> Result<i64, ReaderError> {
+        let fees = Self::sorted_fees(results)?;
+        let middle = fees.len() / 2;
+
+        if fees.len() % 2 == 0 {
+            Ok((fees[middle - 1] + fees[middle]) / 2)
+        } else {
+            Ok(fees[middle])
+        }
+    }
+
+    /// Returns the fee at percentile `pct` using the nearest-rank method.
+    ///
+    /// # Arguments
+    ///
+    /// * `results` - The ledgers to compute the percentile over.
+    /// * `pct` - The percentile, between `0` and `100`.
+    pub fn percentile_fee(results: &[MetaResult], pct: f64) -> Result<i64, ReaderError> {
+        if !(0.0..=100.0).contains(&pct) {
+            return Err(ReaderError::InvalidPercentile(pct));
+        }
+
+        let fees = Self::sorted_fees(results)?;
+        let rank = (pct / 100.0 * fees.len() as f64).ceil() as usize;
+
+        Ok(fees[rank.max(1) - 1])
+    }
+
+    /// Returns the highest fee charged.
+    pub fn max_fee_charged(results: &[MetaResult]) -> Result<i64, ReaderError> {
+        Self::fees(results)?
+            .into_iter()
+            .max()
+            .ok_or(ReaderError::NoTransactions)
+    }
+
+    /// Returns the lowest fee charged.
+    pub fn min_fee_charged(results: &[MetaResult]) -> Result<i64, ReaderError> {
+        Self::fees(results)?
+            .into_iter()
+            .min()
+            .ok_or(ReaderError::NoTransactions)
+    }
+
+    /// Returns the sum of the fees charged, `0` if there are no transactions.
+    pub fn total_fees_collected(results: &[MetaResult]) -> Result<i64, ReaderError> {
+        Ok(Self::fees(results)?.into_iter().sum())
+    }
+
+    fn fees(results: &[MetaResult]) -> Result<Vec<i64>, ReaderError> {
+        let mut fees = Vec::new();
+
+        for result in results.iter().filter(|result| !result.is_catchup_end_sentinel()) {
+            fees.extend(LedgerCloseMetaReader::fee_charged_per_transaction(result)?);
+        }
+
+        Ok(fees)
+    }
+
+    fn sorted_fees(results: &[MetaResult]) -> Result<Vec<i64>, ReaderError> {
+        let mut fees = Self::fees(results)?;
+        if fees.is_empty() {
+            return Err(ReaderError::NoTransactions);
+        }
+
+        fees.sort_unstable();
+        Ok(fees)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stellar_xdr::next::{LedgerCloseMetaV0, LedgerCloseMetaV1};
+
+    use super::*;
+    use crate::LedgerCloseMetaWrapper;
+
+    fn meta_result(ledger_close_meta: LedgerCloseMeta) -> MetaResult {
+        MetaResult {
+            ledger_close_meta: Some(LedgerCloseMetaWrapper { ledger_close_meta }),
+            err: None,
+        }
+    }
+
+    fn v0_with_header(header: LedgerHeader) -> MetaResult {
+        let mut v0 = LedgerCloseMetaV0::default();
+        v0.ledger_header.header = header;
+        meta_result(LedgerCloseMeta::V0(v0))
+    }
+
+    fn supply_header() -> LedgerHeader {
+        LedgerHeader {
+            total_coins: 1_054_439_020_873_472_865,
+            fee_pool: 55_000_004_500,
+            id_pool: 200_003,
+            ..Default::default()
+        }
+    }
+
+    fn assert_supply_accessors(result: &MetaResult) {
+        assert_eq!(
+            LedgerCloseMetaReader::total_coins(result).unwrap(),
+            1_054_439_020_873_472_865
+        );
+        assert_eq!(
+            LedgerCloseMetaReader::fee_pool(result).unwrap(),
+            55_000_004_500
+        );
+        assert_eq!(LedgerCloseMetaReader::id_pool(result).unwrap(), 200_003);
+    }
+
+    #[test]
+    fn supply_accessors_of_v0_meta() {
+        assert_supply_accessors(&v0_with_header(supply_header()));
+    }
+
+    #[test]
+    fn supply_accessors_of_v1_meta() {
+        let mut v1 = LedgerCloseMetaV1::default();
+        v1.ledger_header.header = supply_header();
+
+        assert_supply_accessors(&meta_result(LedgerCloseMeta::V1(v1)));
+    }
+
+    #[test]
+    fn hash_accessors_read_the_same_ledger() {
+        let scp_value = StellarValue {
+            tx_set_hash: Hash([3; 32]),
+            close_time: stellar_xdr::next::TimePoint(1_700_000_000),
+            ..Default::default()
+        };
+        let mut v0 = LedgerCloseMetaV0::default();
+        v0.ledger_header.hash = Hash([1; 32]);
+        v0.ledger_header.header.previous_ledger_hash = Hash([2; 32]);
+        v0.ledger_header.header.scp_value = scp_value.clone();
+        let result = meta_result(LedgerCloseMeta::V0(v0));
+
+        let ledger_hash = LedgerCloseMetaReader::ledger_hash(&result).unwrap();
+        let previous_ledger_hash = LedgerCloseMetaReader::previous_ledger_hash(&result).unwrap();
+        let close_value_hash = LedgerCloseMetaReader::ledger_close_value_hash(&result).unwrap();
+
+        assert_eq!(ledger_hash, [1; 32]);
+        assert_eq!(previous_ledger_hash, [2; 32]);
+        let expected: [u8; 32] = Sha256::digest(scp_value.to_xdr(Limits::none()).unwrap()).into();
+        assert_eq!(close_value_hash, expected);
+        assert_ne!(close_value_hash, ledger_hash);
+        assert_ne!(close_value_hash, previous_ledger_hash);
+    }
+
+    #[test]
+    fn fees_skip_end_sentinels() {
+        let sentinel = MetaResult {
+            ledger_close_meta: None,
+            err: None,
+        };
+        let results = vec![
+            meta_result(LedgerCloseMeta::V0(LedgerCloseMetaV0::default())),
+            sentinel,
+        ];
+        assert_eq!(FeeReader::total_fees_collected(&results).unwrap(), 0);
+
+        let failed = MetaResult {
+            ledger_close_meta: None,
+            err: Some(BufReaderError::ReadXdrNext),
+        };
+        assert!(FeeReader::total_fees_collected(&[failed]).is_err());
+    }
+
+    fn scp_value() -> StellarValue {
+        StellarValue {
+            tx_set_hash: Hash([7; 32]),
+            close_time: stellar_xdr::next::TimePoint(1_700_000_005),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn scp_value_of_v0_meta() {
+        let mut v0 = LedgerCloseMetaV0::default();
+        v0.ledger_header.header.scp_value = scp_value();
+        let result = meta_result(LedgerCloseMeta::V0(v0));
+
+        assert_eq!(
+            LedgerCloseMetaReader::scp_value(&result).unwrap(),
+            scp_value()
+        );
+    }
+
+    #[test]
+    fn scp_value_of_v1_meta() {
+        let mut v1 = LedgerCloseMetaV1::default();
+        v1.ledger_header.header.scp_value = scp_value();
+        let result = meta_result(LedgerCloseMeta::V1(v1));
+
+        assert_eq!(
+            LedgerCloseMetaReader::scp_value(&result).unwrap(),
+            scp_value()
+        );
     }
 }
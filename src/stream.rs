@@ -0,0 +1,96 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_core::Stream;
+use stellar_xdr::next::LedgerCloseMeta;
+
+use crate::{AsyncMetaReceiver, BufReaderError};
+
+/// Stream of ledgers produced by the async runner APIs.
+///
+/// Wraps an [`AsyncMetaReceiver`] so that it can be used with the stream
+/// adapters of the async ecosystem (e.g. `StreamExt` from `tokio-stream` or
+/// `futures`).
+///
+/// The stream ends with the receiver or at the end of the catchup, see
+/// [`MetaResult::is_catchup_end_sentinel`](crate::MetaResult::is_catchup_end_sentinel).
+pub struct LedgerStream {
+    receiver: AsyncMetaReceiver,
+}
+
+impl LedgerStream {
+    /// Creates a new `LedgerStream` reading from `receiver`.
+    pub fn new(receiver: AsyncMetaReceiver) -> Self {
+        Self { receiver }
+    }
+
+    /// Consumes the stream, returning the wrapped receiver.
+    pub fn into_inner(self) -> AsyncMetaReceiver {
+        self.receiver
+    }
+}
+
+impl From<AsyncMetaReceiver> for LedgerStream {
+    fn from(receiver: AsyncMetaReceiver) -> Self {
+        Self::new(receiver)
+    }
+}
+
+impl Stream for LedgerStream {
+    type Item = Result<LedgerCloseMeta, BufReaderError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().receiver.poll_recv(cx).map(|result| {
+            let result = result?;
+            if result.is_catchup_end_sentinel() {
+                return None;
+            }
+
+            Some(match result.ledger_close_meta {
+                Some(wrapper) => Ok(wrapper.ledger_close_meta),
+                None => Err(result.err.unwrap_or(BufReaderError::ReadXdrNext)),
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io, sync::Arc};
+
+    use stellar_xdr::next::LedgerCloseMetaV0;
+
+    use super::*;
+    use crate::{LedgerCloseMetaWrapper, MetaResult};
+
+    async fn next(stream: &mut LedgerStream) -> Option<Result<LedgerCloseMeta, BufReaderError>> {
+        std::future::poll_fn(|cx| Pin::new(&mut *stream).poll_next(cx)).await
+    }
+
+    #[tokio::test]
+    async fn stream_ends_at_catchup_end_sentinel() {
+        let (transmitter, receiver) = tokio::sync::mpsc::unbounded_channel();
+        let ledger_close_meta = LedgerCloseMeta::V0(LedgerCloseMetaV0::default());
+        transmitter
+            .send(Box::new(MetaResult {
+                ledger_close_meta: Some(LedgerCloseMetaWrapper { ledger_close_meta }),
+                err: None,
+            }))
+            .unwrap();
+        transmitter
+            .send(Box::new(MetaResult {
+                ledger_close_meta: None,
+                err: Some(BufReaderError::StdioError(Arc::new(io::Error::from(
+                    io::ErrorKind::UnexpectedEof,
+                )))),
+            }))
+            .unwrap();
+
+        let mut stream = LedgerStream::new(AsyncMetaReceiver::Unbounded(receiver));
+
+        assert!(matches!(next(&mut stream).await, Some(Ok(_))));
+        assert!(next(&mut stream).await.is_none());
+    }
+}
@@ -0,0 +1,138 @@
+//! Test helpers replaying ledgers without spawning stellar-core.
+
+use std::io::Cursor;
+use std::sync::mpsc::Sender;
+
+use async_trait::async_trait;
+use stellar_xdr::next::{LedgerCloseMeta, Limits, WriteXdr};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+    BufReaderError, BufferedLedgerMetaReader, BufferedLedgerMetaReaderMode, MetaResult,
+    MultiThreadBufferedLedgerMetaReader, SingleThreadBufferedLedgerMetaReader,
+    DEFAULT_XDR_RW_DEPTH_LIMIT,
+};
+
+/// Encodes ledgers as framed XDR, the format stellar-core writes to the
+/// metadata output stream.
+///
+/// # Arguments
+///
+/// * `ledgers` - The ledgers to encode, in order.
+///
+/// # Returns
+///
+/// Returns the encoded frames, or `BufReaderError::WriteXdrNext` if a ledger
+/// can't be encoded.
+pub fn encode_frames(ledgers: &[LedgerCloseMeta]) -> Result<Vec<u8>, BufReaderError> {
+    let mut frames = Vec::new();
+
+    for ledger in ledgers {
+        let xdr = ledger
+            .to_xdr(Limits::depth(DEFAULT_XDR_RW_DEPTH_LIMIT))
+            .map_err(|_| BufReaderError::WriteXdrNext)?;
+
+        // the highest bit of the record mark flags the last fragment of the frame.
+        frames.extend_from_slice(&(xdr.len() as u32 | 0x8000_0000).to_be_bytes());
+        frames.extend_from_slice(&xdr);
+    }
+
+    Ok(frames)
+}
+
+/// A `BufferedLedgerMetaReader` replaying the given ledgers instead of
+/// reading them from a stellar-core pipe, e.g. to test ledger processing
+/// logic without running stellar-core.
+///
+/// The ledgers go through the same decoding as the stellar-core output,
+/// and are read through the `SingleThreadBufferedLedgerMetaReader` and
+/// `MultiThreadBufferedLedgerMetaReader` traits.
+pub struct MockBufferedLedgerMetaReader {
+    inner: BufferedLedgerMetaReader,
+}
+
+impl MockBufferedLedgerMetaReader {
+    /// Creates a new `MockBufferedLedgerMetaReader` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - The mode of operation for the reader.
+    /// * `ledgers` - The ledgers to replay, in order.
+    /// * `transmitter` - An optional transmitter for sending metadata results in multi-thread mode.
+    /// * `async_transmitter` - An optional transmitter for sending metadata results in async multi-thread mode.
+    ///
+    /// # Returns
+    ///
+    /// Returns a new `MockBufferedLedgerMetaReader` instance if successful, or a `BufReaderError` if an issue occurs.
+    pub fn new(
+        mode: BufferedLedgerMetaReaderMode,
+        ledgers: Vec<LedgerCloseMeta>,
+        transmitter: Option<Sender<Box<MetaResult>>>,
+        async_transmitter: Option<UnboundedSender<Box<MetaResult>>>,
+    ) -> Result<Self, BufReaderError> {
+        let frames = encode_frames(&ledgers)?;
+
+        let inner = BufferedLedgerMetaReader::new(
+            mode,
+            Box::new(Cursor::new(frames)),
+            transmitter,
+            None,
+            async_transmitter,
+            None,
+            None,
+            Some(ledgers.len()),
+        )?;
+
+        Ok(Self { inner })
+    }
+}
+
+#[async_trait]
+impl SingleThreadBufferedLedgerMetaReader for MockBufferedLedgerMetaReader {
+    fn single_thread_read_ledger_meta_from_pipe(&mut self) -> Result<(), BufReaderError> {
+        self.inner.single_thread_read_ledger_meta_from_pipe()
+    }
+
+    async fn async_single_thread_read_ledger_meta_from_pipe(
+        &mut self,
+    ) -> Result<(), BufReaderError> {
+        self.inner
+            .async_single_thread_read_ledger_meta_from_pipe()
+            .await
+    }
+
+    fn single_thread_read_ledger_meta_from_pipe_with_progress(
+        &mut self,
+        on_ledger: &mut dyn FnMut(u32),
+    ) -> Result<(), BufReaderError> {
+        self.inner
+            .single_thread_read_ledger_meta_from_pipe_with_progress(on_ledger)
+    }
+
+    fn read_meta(&self) -> Result<Vec<MetaResult>, BufReaderError> {
+        self.inner.read_meta()
+    }
+
+    fn drain(&mut self) -> Result<Vec<MetaResult>, BufReaderError> {
+        self.inner.drain()
+    }
+
+    fn clear_buffered(&mut self) -> Result<(), BufReaderError> {
+        self.inner.clear_buffered()
+    }
+}
+
+#[async_trait]
+impl MultiThreadBufferedLedgerMetaReader for MockBufferedLedgerMetaReader {
+    fn multi_thread_read_ledger_meta_from_pipe(&mut self) -> Result<(), BufReaderError> {
+        self.inner.multi_thread_read_ledger_meta_from_pipe()
+    }
+
+    async fn async_multi_thread_read_ledger_meta_from_pipe(
+        &mut self,
+    ) -> Result<(), BufReaderError> {
+        self.inner
+            .async_multi_thread_read_ledger_meta_from_pipe()
+            .await
+    }
+}
@@ -3,7 +3,7 @@ use std::fs::File;
 use std::io::Write;
 use std::path::Path;
 
-use crate::SupportedNetwork;
+use crate::{logging, SupportedNetwork};
 
 const PREDEFINED_FUTURENET_CONFIG: &str = r#"
 # captive core config for futurenet
@@ -89,12 +89,256 @@ HISTORY="curl -sf http://history.stellar.org/prd/core-testnet/core_testnet_001/{
 
 "#;
 
-pub fn generate_predefined_cfg(path: &str, network: SupportedNetwork) {
+/// A validator to include in the generated stellar-core configuration.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ValidatorEntry {
+    /// Name of the validator, also used to reference it in the quorum set.
+    pub name: String,
+
+    /// Home domain of the validator's organization.
+    pub home_domain: String,
+
+    /// Public key of the validator.
+    pub public_key: String,
+
+    /// Peer address of the validator (`host[:port]`).
+    pub address: String,
+
+    /// Command template used to fetch history archive files, e.g.
+    /// `curl -sf https://history.stellar.org/prd/core-live/core_live_001/{0} -o {1}`.
+    pub history_template: String,
+}
+
+/// An explicit quorum set for the generated stellar-core configuration.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QuorumSetConfig {
+    /// Percentage of the validators that must agree.
+    pub threshold: u32,
+
+    /// Names of the validators (see `ValidatorEntry::name`) in the quorum set.
+    pub validators: Vec<String>,
+}
+
+impl QuorumSetConfig {
+    // first validator name of the quorum set that is not in `validators`.
+    pub(crate) fn unknown_validator(&self, validators: &[ValidatorEntry]) -> Option<&str> {
+        self.validators
+            .iter()
+            .find(|name| !validators.iter().any(|validator| &validator.name == *name))
+            .map(String::as_str)
+    }
+}
+
+impl ValidatorEntry {
+    /// Gets the three SDF pubnet validators.
+    pub fn sdf_pubnet() -> Vec<Self> {
+        [
+            (
+                "sdf_1",
+                "GCGB2S2KGYARPVIA37HYZXVRM2YZUEXA6S33ZU5BUDC6THSB62LZSTYH",
+                "core-live-a",
+                "001",
+            ),
+            (
+                "sdf_2",
+                "GCM6QMP3DLRPTAZW2UZPCPX2LF3SXWXKPMP3GKFZBDSF3QZGV2G5QSTK",
+                "core-live-b",
+                "002",
+            ),
+            (
+                "sdf_3",
+                "GABMKJM6I25XI4K7U6XWMULOUQIQ27BCTMLS6BYYSOWKTBUXVRJSXHYQ",
+                "core-live-c",
+                "003",
+            ),
+        ]
+        .into_iter()
+        .map(|(name, public_key, host, archive)| Self {
+            name: name.to_string(),
+            home_domain: "stellar.org".to_string(),
+            public_key: public_key.to_string(),
+            address: format!("{}.stellar.org:11625", host),
+            history_template: format!(
+                "curl -sf https://history.stellar.org/prd/core-live/core_live_{}/{{0}} -o {{1}}",
+                archive
+            ),
+        })
+        .collect()
+    }
+}
+
+// Network-level settings shared by the predefined and custom configs.
+fn network_header(network: SupportedNetwork, unsafe_quorum: bool) -> String {
+    let mut header = format!(
+        r#"
+LOG_COLOR=true
+LOG_FILE_PATH=""
+HTTP_PORT=0
+PUBLIC_HTTP_PORT=false
+DEPRECATED_SQL_LEDGER_STATE=true
+NETWORK_PASSPHRASE="{}"
+
+#EXPERIMENTAL_PRECAUTION_DELAY_META=true
+DATABASE="sqlite3://stellar.db"
+PEER_PORT=11725
+"#,
+        network.passphrase()
+    );
+
+    if unsafe_quorum {
+        header.push_str("\nUNSAFE_QUORUM=true\n");
+    }
+
+    header
+}
+
+fn custom_cfg(
+    network: SupportedNetwork,
+    validators: &[ValidatorEntry],
+    quorum_set: Option<&QuorumSetConfig>,
+) -> String {
+    let mut cfg = network_header(network, quorum_set.is_none());
+
+    if let Some(quorum_set) = quorum_set {
+        // explicit quorum sets can't be mixed with [[VALIDATORS]] entries.
+        let peers: Vec<String> = validators
+            .iter()
+            .map(|validator| format!("\"{}\"", validator.address))
+            .collect();
+        let quorum: Vec<String> = quorum_set
+            .validators
+            .iter()
+            .filter_map(|name| validators.iter().find(|validator| &validator.name == name))
+            .map(|validator| format!("\"{} {}\"", validator.public_key, validator.name))
+            .collect();
+
+        cfg.push_str(&format!("\nKNOWN_PEERS=[{}]\n", peers.join(", ")));
+        cfg.push_str(&format!(
+            "\n[QUORUM_SET]\nTHRESHOLD_PERCENT={}\nVALIDATORS=[{}]\n",
+            quorum_set.threshold,
+            quorum.join(", ")
+        ));
+
+        for validator in validators {
+            cfg.push_str(&format!(
+                "\n[HISTORY.{}]\nget=\"{}\"\n",
+                validator.name, validator.history_template
+            ));
+        }
+    } else {
+        let mut home_domains: Vec<&str> = Vec::new();
+        for validator in validators {
+            if !home_domains.contains(&validator.home_domain.as_str()) {
+                home_domains.push(&validator.home_domain);
+            }
+        }
+
+        for home_domain in home_domains {
+            cfg.push_str(&format!(
+                "\n[[HOME_DOMAINS]]\nHOME_DOMAIN=\"{}\"\nQUALITY=\"MEDIUM\"\n",
+                home_domain
+            ));
+        }
+
+        for validator in validators {
+            cfg.push_str(&format!(
+                "\n[[VALIDATORS]]\nNAME=\"{}\"\nHOME_DOMAIN=\"{}\"\nPUBLIC_KEY=\"{}\"\nADDRESS=\"{}\"\nHISTORY=\"{}\"\n",
+                validator.name,
+                validator.home_domain,
+                validator.public_key,
+                validator.address,
+                validator.history_template
+            ));
+        }
+    }
+
+    cfg
+}
+
+/// Catchup and SCP history settings written to the generated config,
+/// see the matching `IngestionConfig` fields.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CatchupLimits {
+    pub(crate) catchup_complete: Option<bool>,
+    pub(crate) catchup_recent: Option<u32>,
+    pub(crate) max_slots_to_remember: Option<u32>,
+}
+
+impl CatchupLimits {
+    // top-level keys, so they must be written before the first table.
+    fn to_cfg(&self) -> String {
+        let mut cfg = String::new();
+
+        if let Some(catchup_complete) = self.catchup_complete {
+            cfg.push_str(&format!("CATCHUP_COMPLETE={}\n", catchup_complete));
+        }
+        if let Some(catchup_recent) = self.catchup_recent {
+            cfg.push_str(&format!("CATCHUP_RECENT={}\n", catchup_recent));
+        }
+        if let Some(max_slots) = self.max_slots_to_remember {
+            cfg.push_str(&format!("MAX_SLOTS_TO_REMEMBER={}\n", max_slots));
+        }
+
+        cfg
+    }
+}
+
+// Name of the curl wrapper adding the history archive headers, written
+// next to `stellar-core.cfg`.
+const HISTORY_CURL_SCRIPT: &str = "history-curl.sh";
+
+// Quotes `value` as a single shell word.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+fn write_history_curl_script(path: &Path, headers: &[(String, String)]) {
+    let mut script = String::from("#!/bin/sh\nexec curl");
+    for (name, value) in headers {
+        let header = format!("{}: {}", name, value);
+        script.push_str(&format!(" -H {}", shell_quote(&header)));
+    }
+    script.push_str(" \"$@\"\n");
+
+    fs::write(path, script).expect("cannot write history curl script");
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        fs::set_permissions(path, fs::Permissions::from_mode(0o700))
+            .expect("cannot make history curl script executable");
+    }
+}
+
+/// Generates the `stellar-core.cfg` for the given network in `path`.
+///
+/// When `validators` is empty the predefined single-validator config
+/// of the network is used. Otherwise the given validators are configured,
+/// either through an explicit `quorum_set` or automatic quorum generation
+/// from their home domains. `UNSAFE_QUORUM` is only set when no quorum
+/// set is provided. The HTTP port is disabled unless `http_port` is set.
+///
+/// When `history_archive_headers` is not empty, a curl wrapper script
+/// sending them is written to `path` and replaces `curl` in the history
+/// commands starting with `curl`. The settings of `catchup_limits` that
+/// are set are added to the network settings.
+pub(crate) fn generate_predefined_cfg(
+    path: &str,
+    network: SupportedNetwork,
+    validators: &[ValidatorEntry],
+    quorum_set: Option<&QuorumSetConfig>,
+    http_port: Option<u16>,
+    history_archive_headers: &[(String, String)],
+    catchup_limits: &CatchupLimits,
+) {
     match fs::create_dir(path) {
-        Ok(_) => println!("Directory created successfully."),
+        Ok(_) => logging::info!("Directory created successfully."),
         Err(err) => {
             if err.kind() != std::io::ErrorKind::AlreadyExists {
-                eprintln!("Error creating directory: {}", err);
+                logging::error!("Error creating directory: {}", err);
                 panic!();
             }
         }
@@ -103,20 +347,44 @@ pub fn generate_predefined_cfg(path: &str, network: SupportedNetwork) {
     let mut cfg =
         File::create(Path::new(path).join("stellar-core.cfg")).expect("cannot create file");
 
-    match network {
-        SupportedNetwork::Futurenet => {
-            cfg.write_all(PREDEFINED_FUTURENET_CONFIG.as_bytes())
-                .expect("cannot write to file");
+    let contents = if !validators.is_empty() {
+        custom_cfg(network, validators, quorum_set)
+    } else {
+        match network {
+            SupportedNetwork::Futurenet => PREDEFINED_FUTURENET_CONFIG.to_string(),
+            SupportedNetwork::Pubnet => PREDEFINED_PUBNET_CONFIG.to_string(),
+            SupportedNetwork::Testnet => PREDEFINED_TESTNET_CONFIG.to_string(),
         }
+    };
 
-        SupportedNetwork::Pubnet => {
-            cfg.write_all(PREDEFINED_PUBNET_CONFIG.as_bytes())
-                .expect("cannot write to file");
-        }
+    // every config is generated with `HTTP_PORT=0`.
+    let contents = match http_port {
+        Some(port) => contents.replacen("HTTP_PORT=0", &format!("HTTP_PORT={}", port), 1),
+        None => contents,
+    };
 
-        SupportedNetwork::Testnet => {
-            cfg.write_all(PREDEFINED_TESTNET_CONFIG.as_bytes())
-                .expect("cannot write to file")
-        }
-    }
+    // every config sets `PEER_PORT` before its first table.
+    let contents = contents.replacen(
+        "PEER_PORT=11725\n",
+        &format!("PEER_PORT=11725\n{}", catchup_limits.to_cfg()),
+        1,
+    );
+
+    // history commands are run from stellar-core's working directory,
+    // so the script is referenced by its absolute path.
+    let contents = if history_archive_headers.is_empty() {
+        contents
+    } else {
+        let script = fs::canonicalize(path)
+            .unwrap_or_else(|_| Path::new(path).to_path_buf())
+            .join(HISTORY_CURL_SCRIPT);
+        write_history_curl_script(&script, history_archive_headers);
+
+        let script = script.display().to_string();
+        contents
+            .replace("HISTORY=\"curl ", &format!("HISTORY=\"{} ", script))
+            .replace("get=\"curl ", &format!("get=\"{} ", script))
+    };
+
+    cfg.write_all(contents.as_bytes()).expect("cannot write to file");
 }